@@ -1,7 +1,110 @@
 ---@class FontReplaceConfig
+---@field terminal "'auto'"|"'kitty'"|"'alacritty'"|"'rio'"|"'contour'"|"'windows_terminal'"|"'guifont'"
 ---@field kitty_conf_path string
+---@field alacritty_conf_path string
+---@field rio_conf_path string
+---@field contour_conf_path string
+---@field windows_terminal_conf_path string
+---@field targets string[] Backends changes go to, `terminal` or `guifont`
+---@field neovide NekifochNeovideConfig
+---@field dpi NekifochDpiConfig
+---@field reload_strategy "'auto'"|"'signal'"|"'remote'"
+---@field reload_all boolean Signal every kitty instance, not only this one
+---@field force_kitty boolean Act as if running inside kitty, e.g. over ssh
+---@field live_preview boolean
+---@field size_step number Increment of size_up / size_down
+---@field size_min number
+---@field size_max number
+---@field size_debounce integer Milliseconds before toggled features are written
+---@field backup NekifochBackupConfig
+---@field managed NekifochManagedConfig
+---@field notify boolean Messages through vim.notify, `false` echoes them
+---@field log { level: "'debug'"|"'info'"|"'warn'"|"'error'"|"'off'" }
+---@field confirm boolean Show a diff and ask before writing the config
+---@field profiles table<string, NekifochProfile>
+---@field profiles_by_host table<string, NekifochProfile> Applied at setup on the machine with that hostname
+---@field rules NekifochRule[] Fonts by time of day or condition, later rules win
+---@field by_colorscheme table<string, NekifochProfile> Applied when that colorscheme loads
+---@field bundles table<string, NekifochBundle> kitty theme and font switched together
+---@field picker "'nui'"|"'ui_select'"
+---@field cache NekifochCacheConfig
+---@field prefetch boolean List fonts in the background at setup
+---@field watch_config boolean Notice edits of the config made elsewhere
+---@field on_pre_change fun(change: NekifochFont): boolean|nil Return `false` to cancel
+---@field on_post_change fun(change: NekifochFont)|nil
 ---@field which_key WhichKeyNekifoch
----@field borders "'none'"|"'single'"|"'double'"|"'rounded'"|"'solid'"|"'shadow'"
+---@field menu_items NekifochMenuItem[] Added to the end of the main menu
+---@field window NekifochWindowConfig
+---@field recent NekifochRecentConfig
+---@field cycle "'all'"|"'favorites'"|"'recent'" Fonts next_font / prev_font go through
+---@field keymaps NekifochKeymaps
+---@field default_keymaps boolean `false` keeps only the keys from `keymaps`
+---@field borders NekifochBorder|table<NekifochWindowKind|'default', NekifochBorder>
+---@field preview PreviewNekifoch
+
+---@alias NekifochBorder "'none'"|"'single'"|"'double'"|"'rounded'"|"'solid'"|"'shadow'"|string[]
+
+---@class NekifochDpiConfig
+---@field enable boolean Size the font for the display on FocusGained
+---@field sizes { max: number|nil, size: number }[] First range the DPI fits
+
+---@class NekifochNeovideConfig
+---@field scale "'off'"|"'also'"|"'only'" size_up / size_down also or only step `g:neovide_scale_factor`
+---@field step number
+
+---@class PreviewNekifoch
+---@field sample string
+---@field images boolean Render the sample in the pane beside the font window
+---@field sizes number[]
+
+---@class NekifochBackupConfig
+---@field enable boolean
+---@field dir string
+---@field keep integer Backups kept per config file
+
+---@class NekifochManagedConfig
+---@field enable boolean Write to an include file instead of kitty.conf
+---@field file string Next to kitty.conf
+
+---@class NekifochCacheConfig
+---@field ttl integer Seconds the font list is kept on disk, 0 disables it
+---@field watch boolean Rebuild the font list when font directories change
+
+---@class NekifochRecentConfig
+---@field max integer Fonts remembered
+---@field show integer Recent fonts above the font list, 0 hides them
+
+---@class NekifochWindowConfig
+---@field width integer|nil Width of every window instead of its own
+---@field height integer|nil Height of list windows instead of fitting the list
+---@field row number|string|nil Overrides the row `anchor` picks
+---@field col number|string|nil Overrides the column `anchor` picks
+---@field anchor "'center'"|"'cursor'"|"'top_right'"
+---@field winblend integer
+---@field zindex integer|nil
+---@field max_height integer Lines shown before a list window scrolls
+---@field hints boolean Show keymap hints in window footers
+---@field split boolean Show the highlighted family beside the font window
+---@field list_max_width integer Widest the columns of the list window get
+
+---@class NekifochKeymaps Keys of window actions, a key or a list of keys
+---@field confirm string|string[]
+---@field back string|string[] Return to the previous window
+---@field close string|string[]
+---@field next string|string[]
+---@field prev string|string[]
+---@field filter string|string[] Filter the font list
+---@field favorite string|string[] Toggle the font under the cursor as favorite
+---@field nerd string|string[] Show only Nerd Fonts or every font
+---@field details string|string[] Styles and files of the font under the cursor
+---@field coverage string|string[] Check glyphs of some text in the details
+---@field preview string|string[] Follow the font under the cursor in kitty
+---@field size_up string|string[]
+---@field size_down string|string[]
+---@field size_fine_up string|string[] Steps of 0.1 in the size window
+---@field size_fine_down string|string[]
+---@field size_coarse_up string|string[] Steps of 2 in the size window
+---@field size_coarse_down string|string[]
 
 ---@class WhichKeyNekifoch
 ---@field enable boolean
@@ -9,13 +112,106 @@
 -- TODO: add reading from NEVIRAIDE config and config path to NEVIRAIDEfile
 local M = {}
 
+local detect = require('nekifoch.detect')
+
 ---@type FontReplaceConfig
 M.config = {
-  kitty_conf_path = vim.env.HOME .. '/.config/kitty/kitty.conf',
+  terminal = 'auto',
+  kitty_conf_path = detect.config_path('kitty'),
+  alacritty_conf_path = detect.config_path('alacritty'),
+  rio_conf_path = detect.config_path('rio'),
+  contour_conf_path = detect.config_path('contour'),
+  windows_terminal_conf_path = detect.config_path('windows_terminal'),
+  targets = { 'terminal' },
+  neovide = {
+    scale = 'off',
+    step = 0.1,
+  },
+  dpi = {
+    enable = false,
+    sizes = {
+      { max = 120, size = 12 },
+      { max = 180, size = 14 },
+      { size = 16 },
+    },
+  },
+  reload_strategy = 'auto',
+  reload_all = false,
+  force_kitty = false,
+  live_preview = true,
+  size_step = 1.0,
+  size_min = 6,
+  size_max = 32,
+  size_debounce = 400,
+  backup = {
+    enable = true,
+    dir = vim.fn.stdpath('data') .. '/nekifoch/backups',
+    keep = 10,
+  },
+  managed = {
+    enable = false,
+    file = 'nekifoch.conf',
+  },
+  confirm = false,
+  notify = true,
+  log = {
+    level = 'warn',
+  },
+  profiles = {},
+  profiles_by_host = {},
+  rules = {},
+  by_colorscheme = {},
+  bundles = {},
+  picker = 'nui',
+  cache = {
+    ttl = 7 * 24 * 60 * 60,
+    watch = false,
+  },
+  prefetch = false,
+  watch_config = false,
   which_key = {
     enable = false,
   },
+  menu_items = {},
+  cycle = 'all',
+  recent = {
+    max = 10,
+    show = 3,
+  },
+  window = {
+    anchor = 'center',
+    winblend = 0,
+    max_height = 15,
+    hints = true,
+    split = true,
+    list_max_width = 120,
+  },
+  keymaps = {
+    confirm = { '<CR>', '<Space>', '<2-LeftMouse>' },
+    back = '<Esc>',
+    close = { 'q', '<C-c>' },
+    next = { 'j', '<Down>', '<Tab>' },
+    prev = { 'k', '<Up>', '<S-Tab>' },
+    filter = '/',
+    favorite = 'f',
+    nerd = 'n',
+    details = 'K',
+    coverage = 'c',
+    preview = 'p',
+    size_up = { 'k', '<Up>', '<ScrollWheelUp>' },
+    size_down = { 'j', '<Down>', '<ScrollWheelDown>' },
+    size_fine_up = 'l',
+    size_fine_down = 'h',
+    size_coarse_up = 'K',
+    size_coarse_down = 'J',
+  },
+  default_keymaps = true,
   borders = 'rounded',
+  preview = {
+    sample = 'The quick brown fox jumps over the lazy dog 0O 1lI {}[]() => !=',
+    sizes = { 10, 11, 12, 13, 14 },
+    images = false,
+  },
 }
 
 --- Check if a plugin is installed
@@ -29,9 +225,37 @@ end
 --- Setup font configuration
 ---@param config table
 function M.setup(config)
-  M.config = vim.tbl_deep_extend('force', M.config, config or {})
+  config = config or {}
+  local problems = require('nekifoch.validate').check(config, M.config)
+  if #problems > 0 then
+    require('nekifoch.notify').warn(problems, 'Nekifoch setup')
+  end
+  local keymaps = M.config.keymaps
+  M.config = vim.tbl_deep_extend('force', M.config, config)
+  -- Key lists replace the defaults instead of merging by index
+  if config.keymaps or config.default_keymaps == false then
+    M.config.keymaps = vim.tbl_extend(
+      'force',
+      config.default_keymaps == false and {} or keymaps,
+      config.keymaps or {}
+    )
+  end
+  require('nekifoch.settings').load_profiles()
   require('nekifoch.command').setup()
+  require('nekifoch.plug').setup()
+  require('nekifoch.ui').setup_highlights()
   -- require('nekifoch.command')
+  if M.config.cache.watch then require('nekifoch.cache').watch() end
+  -- The first picker opens at once instead of waiting for fc-list and kitty
+  if M.config.prefetch then require('nekifoch.cache').get_async() end
+  if M.config.watch_config then require('nekifoch.watch').start() end
+  if M.config.dpi.enable then require('nekifoch.dpi').start() end
+  if #M.config.rules > 0 then require('nekifoch.rules').start() end
+  if not vim.tbl_isempty(M.config.by_colorscheme) then
+    require('nekifoch.colorscheme').start()
+  end
+  -- Don't hold up startup with reading and writing the config
+  vim.schedule(function() require('nekifoch.hosts').apply() end)
 
   -- Check if which-key and plenary.nvim are installed
   if
@@ -41,14 +265,85 @@ function M.setup(config)
   then
     require('nekifoch.which_key')
   elseif M.config.which_key.enable then
-    print(
+    require('nekifoch.notify').warn(
       "WhichKey functionality is enabled but plugin 'which-key' and/or 'plenary.nvim' are not installed. Additional functionality will not be available."
     )
   else
-    print('WhichKey functionality is disabled.')
+    require('nekifoch.notify').info('WhichKey functionality is disabled.')
   end
 end
 
-function M.cmd(name) require('nekifoch.command').cmd(name) end
+--- Current font settings
+---@return { family: string|nil, size: number|nil, bold: string|nil, italic: string|nil, bold_italic: string|nil }|nil current
+---@return string|nil err
+function M.get_current()
+  local current, err = require('nekifoch.command').execute('check', {})
+  if not current then return nil, err end
+  return {
+    family = current.font,
+    size = tonumber(current.size),
+    bold = current.bold,
+    italic = current.italic,
+    bold_italic = current.bold_italic,
+  }
+end
+
+--- Current family and size for a statusline, e.g. `Fira Code 13`. Reads
+--- the config again only after it changed
+---@return string
+function M.statusline()
+  local ok, current = pcall(require('nekifoch.backend').current().get)
+  if not ok or not current.font then return '' end
+  return current.font .. (current.size and (' ' .. current.size) or '')
+end
+
+--- Set the font family and reload the terminal
+---@param name string Font family
+---@return NekifochFont|nil change
+---@return string|nil err
+function M.set_font(name)
+  return require('nekifoch.command').execute('set_font', { name })
+end
+
+--- Set the font size and reload the terminal
+---@param size number
+---@return NekifochFont|nil change
+---@return string|nil err
+function M.set_size(size)
+  return require('nekifoch.command').execute('set_size', { tostring(size) })
+end
+
+--- Set the font family and size together with a single reload
+---@param opts { family: string|nil, size: number|string|nil }
+---@return NekifochFont|nil change
+---@return string|nil err
+function M.apply(opts)
+  local args = {}
+  if opts.family then table.insert(args, opts.family) end
+  if opts.size then table.insert(args, tostring(opts.size)) end
+  return require('nekifoch.command').execute('apply', args)
+end
+
+--- Support another terminal, or a config generated some other way.
+--- Register before `setup()` to use the name in `terminal` or `targets`
+---@param spec NekifochBackendSpec
+function M.register_backend(spec)
+  require('nekifoch.backend').register(spec)
+end
+
+--- Fonts the terminal can use
+---@return string[] fonts
+function M.list_fonts()
+  return (require('nekifoch.command').execute('list', {}))
+end
+
+--- Run a `:Nekifoch` subcommand from Lua and return its result
+---@param name string Subcommand name
+---@param ... string Subcommand arguments
+---@return any result
+---@return string|nil err
+function M.cmd(name, ...)
+  return require('nekifoch.command').execute(name, { ... })
+end
 
 return M
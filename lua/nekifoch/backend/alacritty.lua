@@ -0,0 +1,99 @@
+local util = require('nekifoch.utils')
+local toml = require('nekifoch.toml')
+
+---@type NekifochBackend
+local M = {
+  name = 'alacritty',
+  kitty_fonts = false,
+}
+
+---@param style string `normal`, `bold`, `italic` or `bold_italic`
+---@return NekifochTomlKey[]
+local function family_keys(style)
+  return {
+    { 'font.' .. style, '^%s*family%s*=', 'family' },
+    { 'font', '^%s*' .. style .. '%.family%s*=', style .. '%.family' },
+    { 'font', '^%s*' .. style .. '%s*=%s*{.*family%s*=', 'family' },
+    {
+      '',
+      '^%s*font%.' .. style .. '%.family%s*=',
+      'font%.' .. style .. '%.family',
+    },
+  }
+end
+
+local size_keys = {
+  { 'font', '^%s*size%s*=', 'size' },
+  { '', '^%s*font%.size%s*=', 'font%.size' },
+}
+
+M.config_path = function()
+  return require('nekifoch').config.alacritty_conf_path
+end
+
+---@return string[]
+local function read_lines()
+  return vim.split(util.read_file(M.config_path()), '\n')
+end
+
+---@param lines string[]
+local function write_lines(lines)
+  util.write_file(M.config_path(), table.concat(lines, '\n'))
+end
+
+M.get = function()
+  local lines = read_lines()
+  local font = {}
+
+  for _, style in ipairs({ 'normal', 'bold', 'italic', 'bold_italic' }) do
+    local index, key = toml.locate(lines, family_keys(style))
+    if index then
+      local line = lines[index]
+      font[style == 'normal' and 'font' or style] = toml.string(line, key)
+    end
+  end
+
+  local index, key = toml.locate(lines, size_keys)
+  if index then
+    font.size = lines[index]:match(key .. '%s*=%s*([%d%.]+)')
+  end
+
+  return font
+end
+
+M.set_font = function(family, style)
+  style = style or 'normal'
+  local lines = read_lines()
+  local value = '"' .. family .. '"'
+  local action = toml.set_value(
+    lines,
+    family_keys(style),
+    'font.' .. style,
+    'family = ' .. value,
+    value
+  )
+  write_lines(lines)
+  return action
+end
+
+M.set_size = function(size)
+  local lines = read_lines()
+  local value = toml.format_size(size)
+  local action =
+    toml.set_value(lines, size_keys, 'font', 'size = ' .. value, value)
+  write_lines(lines)
+  return action
+end
+
+M.unset_size = function()
+  local lines = read_lines()
+  local index = toml.locate(lines, size_keys)
+  if not index then return end
+  table.remove(lines, index)
+  write_lines(lines)
+end
+
+-- Alacritty reloads its configuration on change (`live_config_reload`)
+M.reload = function(_) end
+
+return M
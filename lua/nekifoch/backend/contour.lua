@@ -0,0 +1,170 @@
+local util = require('nekifoch.utils')
+
+---@type NekifochBackend
+local M = {
+  name = 'contour',
+  kitty_fonts = false,
+}
+
+-- contour.yml keys of the font styles under `font`
+local keys = {
+  normal = 'regular',
+  bold = 'bold',
+  italic = 'italic',
+  bold_italic = 'bold_italic',
+}
+
+M.config_path = function()
+  return require('nekifoch').config.contour_conf_path
+end
+
+---@return string[]
+local function read_lines()
+  return vim.split(util.read_file(M.config_path()), '\n')
+end
+
+---@param lines string[]
+local function write_lines(lines)
+  util.write_file(M.config_path(), table.concat(lines, '\n'))
+end
+
+---@param line string
+---@return integer|nil indent
+---@return string|nil key
+local function mapping_key(line)
+  local spaces, key = line:match('^(%s*)([%w_%-]+)%s*:')
+  if not key then return nil end
+  return #spaces, key
+end
+
+---Line of the YAML key at `path`, following indentation. `*` matches any
+---key
+---@param lines string[]
+---@param path string[]
+---@return integer|nil index
+---@return integer|nil indent
+local function find(lines, path)
+  local depth, parent, child = 1, -1, nil
+  for i, line in ipairs(lines) do
+    local indent, key = mapping_key(line)
+    if indent then
+      -- Back at the parent's level, the key isn't there
+      if indent <= parent then return nil end
+      child = child or indent
+      local wanted = path[depth]
+      if indent == child and (wanted == '*' or wanted == key) then
+        if depth == #path then return i, indent end
+        depth, parent, child = depth + 1, indent, nil
+      end
+    end
+  end
+end
+
+---`font` of the profile in use, `default_profile` or the first one
+---@param lines string[]
+---@return string[]
+local function font_path(lines)
+  local profile = '*'
+  local index = find(lines, { 'default_profile' })
+  if index then
+    local name = lines[index]:match(':%s*["\']?([%w_%-]+)')
+    if name and find(lines, { 'profiles', name }) then profile = name end
+  end
+  return { 'profiles', profile, 'font' }
+end
+
+---Scalar value on `line`, without quotes and comments
+---@param line string
+---@return string
+local function scalar(line)
+  local value = line:match(':%s*(.-)%s*$')
+  value = value:match('^"(.*)"') or value:match("^'(.*)'")
+    or vim.trim(value:gsub('%s+#.*$', ''))
+  return value
+end
+
+---Set `key` below the mapping at `path`, adding it when missing
+---@param lines string[]
+---@param path string[]
+---@param key string
+---@param value string YAML value
+---@return NekifochEditAction
+local function set_value(lines, path, key, value)
+  local index = find(lines, vim.list_extend(vim.list_slice(path), { key }))
+  if index then
+    local prefix, rest = lines[index]:match('^(.-:%s*)(.*)$')
+    local comment = rest:match('%s+#.*$') or ''
+    lines[index] = prefix .. value .. comment
+    return 'replaced'
+  end
+
+  local parent, indent = find(lines, path)
+  if not parent then
+    error(table.concat(path, '.') .. ' not found in ' .. M.config_path(), 0)
+  end
+  table.insert(
+    lines,
+    parent + 1,
+    string.rep(' ', indent + 2) .. key .. ': ' .. value
+  )
+  return 'appended'
+end
+
+M.get = function()
+  local lines = read_lines()
+  local path = font_path(lines)
+  local font = {}
+
+  for style, key in pairs(keys) do
+    local style_path = vim.list_extend(vim.list_slice(path), { key })
+    local index = find(lines, vim.list_extend(vim.list_slice(style_path), {
+      'family',
+    })) or find(lines, style_path)
+    -- `regular: "Fira Code"` is the family alone
+    local family = index and scalar(lines[index])
+    if family and family ~= '' then
+      font[style == 'normal' and 'font' or style] = family
+    end
+  end
+
+  local index = find(lines, vim.list_extend(vim.list_slice(path), { 'size' }))
+  if index then font.size = scalar(lines[index]) end
+
+  return font
+end
+
+M.set_font = function(family, style)
+  local lines = read_lines()
+  local path = vim.list_extend(font_path(lines), { keys[style or 'normal'] })
+  local index = find(lines, path)
+  local action
+  if index and scalar(lines[index]) ~= '' then
+    -- A plain family like `regular: "Fira Code"`
+    local prefix = lines[index]:match('^(.-:%s*)')
+    lines[index] = prefix .. '"' .. family .. '"'
+    action = 'replaced'
+  elseif index then
+    action = set_value(lines, path, 'family', '"' .. family .. '"')
+  else
+    action = set_value(
+      lines,
+      vim.list_slice(path, 1, #path - 1),
+      path[#path],
+      '"' .. family .. '"'
+    )
+  end
+  write_lines(lines)
+  return action
+end
+
+M.set_size = function(size)
+  local lines = read_lines()
+  local action = set_value(lines, font_path(lines), 'size', tostring(size))
+  write_lines(lines)
+  return action
+end
+
+-- Contour reloads its configuration on change with `live_config: true`
+M.reload = function(_) end
+
+return M
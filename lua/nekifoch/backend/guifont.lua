@@ -0,0 +1,73 @@
+-- `&guifont` of GUI frontends like Neovide, neovim-qt or nvim-gtk
+---@type NekifochBackend
+local M = {
+  name = 'guifont',
+  kitty_fonts = false,
+}
+
+-- No file, the option is set in this Neovim only
+M.config_path = function() return '' end
+
+---First font of `&guifont` split into family and the `:h` size
+---@return string|nil family
+---@return string|nil size
+---@return string[] attributes Other `:` attributes, e.g. `b`
+local function parse()
+  -- Fallback fonts follow unescaped commas
+  local first = vim.o.guifont:gsub('\\,', '\1'):match('^[^,]*'):gsub('\1', ',')
+  local parts = vim.split(first, ':', { plain = true })
+  local family = parts[1]:gsub('\\ ', ' ')
+  local size, attributes = nil, {}
+  for i = 2, #parts do
+    local height = parts[i]:match('^h([%d%.]+)$')
+    if height then
+      size = height
+    elseif parts[i] ~= '' then
+      table.insert(attributes, parts[i])
+    end
+  end
+  return family ~= '' and family or nil, size, attributes
+end
+
+---@param family string|nil
+---@param size string|nil
+---@param attributes string[]
+local function write(family, size, attributes)
+  if require('nekifoch.utils').dry then return end
+  local value = (family or ''):gsub(',', '\\,')
+  if size then value = value .. ':h' .. size end
+  for _, attribute in ipairs(attributes) do
+    value = value .. ':' .. attribute
+  end
+  require('nekifoch.log').info('guifont=' .. value)
+  vim.o.guifont = value
+end
+
+M.get = function()
+  local family, size = parse()
+  return { font = family, size = size }
+end
+
+---Styles have no option of their own, the GUI picks the faces
+M.set_font = function(family, style)
+  if style then return 'replaced' end
+  local _, size, attributes = parse()
+  write(family, size, attributes)
+  return 'replaced'
+end
+
+M.set_size = function(size)
+  local family, _, attributes = parse()
+  write(family, tostring(size), attributes)
+  return 'replaced'
+end
+
+M.unset_size = function()
+  local family, _, attributes = parse()
+  write(family, nil, attributes)
+end
+
+-- Setting the option applies it
+M.reload = function(_) end
+
+return M
@@ -0,0 +1,111 @@
+---@alias NekifochStyle "'bold'"|"'italic'"|"'bold_italic'"
+
+---@class NekifochBackend
+---@field name string
+---@field kitty_fonts boolean Offer only fonts listed by `kitty +list-fonts`
+---@field config_path fun(): string Terminal configuration file
+---@field get fun(): NekifochFont Current font settings
+---@field set_font fun(family: string, style: NekifochStyle|nil, spec: table<string, string>|nil): NekifochEditAction Only kitty writes `spec`
+---@field set_size fun(size: number): NekifochEditAction
+---@field unset_size? fun() Remove the size so the terminal default applies
+---@field set_option? fun(option: string, value: string): NekifochEditAction|nil Set another option, `default` removes it
+---@field modify_font? fun(what: string, value: string): NekifochEditAction|nil Adjust cell metrics, `default` removes the adjustment
+---@field set_theme? fun(theme: string): table<string, string|false> Switch the color theme, applied by the next reload, returning the files it changed as they were
+---@field reload fun(change: NekifochFont|nil): boolean|nil, string|nil Apply `change` in the terminal, `false` and the error when it was rejected
+---@field preview_font? fun(family: string): boolean Show without saving
+---@field cancel_preview? fun() Return to the saved font
+---@field list_filter? fun(fonts: string[]): string[] Fonts the terminal takes
+
+local M = {}
+
+---Name of the backend to use, resolving `'auto'` from the environment
+---@return string
+M.name = function()
+  local name = require('nekifoch').config.terminal
+  if name == 'auto' then
+    return require('nekifoch.detect').terminal() or 'kitty'
+  end
+  return name
+end
+
+---Backends added with `register_backend()`, by name
+---@type table<string, NekifochBackend>
+M.registered = {}
+
+---@class NekifochBackendSpec
+---@field name string
+---@field read fun(): NekifochFont Current family, size and styles
+---@field write fun(field: "'font'"|"'size'"|NekifochStyle, value: string) Change one setting, `size` may be `default`
+---@field reload fun(change: NekifochFont|nil)|nil
+---@field list_filter fun(fonts: string[]): string[] Fonts the terminal takes
+---@field config_path string|fun(): string|nil
+
+---Add a backend for a terminal nekifoch doesn't know, usable in `terminal`
+---and `targets` under its name
+---@param spec NekifochBackendSpec
+M.register = function(spec)
+  vim.validate({
+    name = { spec.name, 'string' },
+    read = { spec.read, 'function' },
+    write = { spec.write, 'function' },
+    reload = { spec.reload, 'function', true },
+    list_filter = { spec.list_filter, 'function', true },
+  })
+  local path = spec.config_path
+  M.registered[spec.name] = {
+    name = spec.name,
+    kitty_fonts = false,
+    list_filter = spec.list_filter,
+    config_path = function()
+      if type(path) == 'function' then return path() end
+      return path or ''
+    end,
+    get = spec.read,
+    set_font = function(family, style)
+      spec.write(style or 'font', family)
+      return 'replaced'
+    end,
+    set_size = function(size)
+      spec.write('size', tostring(size))
+      return 'replaced'
+    end,
+    unset_size = function() spec.write('size', 'default') end,
+    reload = function(change)
+      if spec.reload then spec.reload(change) end
+    end,
+  }
+end
+
+---@param name string
+---@return NekifochBackend
+local function load(name)
+  if M.registered[name] then return M.registered[name] end
+  local ok, backend = pcall(require, 'nekifoch.backend.' .. name)
+  if not ok then error('Unsupported terminal: ' .. name) end
+  return backend
+end
+
+---Backends changes are written to, from `targets` with `terminal` standing
+---for the one selected with `setup({ terminal = ... })`
+---@return string[]
+M.targets = function()
+  local names = {}
+  for _, target in ipairs(require('nekifoch').config.targets) do
+    local name = target == 'terminal' and M.name() or target
+    if not vim.tbl_contains(names, name) then table.insert(names, name) end
+  end
+  if #names == 0 then names = { M.name() } end
+  return names
+end
+
+---First of `targets`, the backend fonts are read from and written to
+---@return NekifochBackend
+M.current = function() return load(M.targets()[1]) end
+
+---The other `targets`, following the family and size of `current`
+---@return NekifochBackend[]
+M.others = function()
+  return vim.tbl_map(load, vim.list_slice(M.targets(), 2))
+end
+
+return M
@@ -0,0 +1,248 @@
+local util = require('nekifoch.utils')
+
+---@type NekifochBackend
+local M = {
+  name = 'kitty',
+  kitty_fonts = true,
+}
+
+M.config_path = function() return require('nekifoch').config.kitty_conf_path end
+
+---Neovim runs inside kitty, or `force_kitty` says to act as if it did,
+---e.g. to edit kitty.conf over ssh
+---@return boolean
+M.inside = function()
+  return require('nekifoch').config.force_kitty
+    or vim.env.KITTY_PID ~= nil
+    or vim.env.KITTY_WINDOW_ID ~= nil
+end
+
+M.get = function() return util.get() end
+
+M.set_font = function(family, style, spec)
+  if style then return util.replace_style_font(style, family, spec) end
+  return util.replace_font('font_family', family, spec)
+end
+
+M.set_size = function(size) return util.replace_font_size(size) end
+
+---Switch the theme with the themes kitten, which writes current-theme.conf
+---and includes it from kitty.conf. It is picked up by the next reload
+---@param theme string
+---@return table<string, string|false> previous Files the kitten may change,
+---`false` for new ones
+M.set_theme = function(theme)
+  if util.dry then return {} end
+  util.require_executable('kitty', 'kitty')
+  local path = util.resolve(M.config_path())
+  local previous = {}
+  for _, file in ipairs({
+    path,
+    vim.fn.fnamemodify(path, ':h') .. '/current-theme.conf',
+  }) do
+    local readable, content = pcall(util.read_file, file)
+    previous[file] = readable and content
+    require('nekifoch.backup').create(file)
+  end
+  local output = util.system({
+    'env',
+    'KITTY_CONFIG_DIRECTORY=' .. vim.fn.fnamemodify(path, ':h'),
+    'kitty',
+    '+kitten',
+    'themes',
+    '--reload-in=none',
+    '--config-file-name=' .. vim.fn.fnamemodify(path, ':t'),
+    theme,
+  })
+  if vim.v.shell_error ~= 0 then
+    util.restore_files(previous)
+    error('kitty themes failed: ' .. vim.trim(output), 0)
+  end
+  util.invalidate()
+  return previous
+end
+
+M.unset_size = function() util.comment_option('font_size') end
+
+M.set_option = function(option, value)
+  if value == 'default' then return util.comment_option(option) end
+  return util.replace_option(option, value)
+end
+
+M.modify_font = function(what, value)
+  if value == 'default' then return util.comment_modify_font(what) end
+  return util.replace_modify_font(what, value)
+end
+
+---@return "'signal'"|"'remote'"
+local function strategy()
+  local configured = require('nekifoch').config.reload_strategy
+  if configured == 'auto' then
+    return vim.env.KITTY_LISTEN_ON and 'remote' or 'signal'
+  end
+  return configured
+end
+
+---@param args string[]
+---@return string[]
+local function remote_cmd(args)
+  local cmd = { vim.fn.executable('kitten') == 1 and 'kitten' or 'kitty', '@' }
+  if vim.env.KITTY_LISTEN_ON then
+    vim.list_extend(cmd, { '--to', vim.env.KITTY_LISTEN_ON })
+  end
+  return vim.list_extend(cmd, args)
+end
+
+-- Output of remote control failing to reach kitty, rather than kitty
+-- refusing the command
+local transport_errors = {
+  'connect',
+  'refused',
+  'remote control',
+  'permission denied',
+  'no such file',
+  'timed out',
+  'timeout',
+  'socket',
+  'password',
+}
+
+---@param output string
+---@return boolean
+local function transport_error(output)
+  local lower = output:lower()
+  if lower == '' then return true end
+  for _, pattern in ipairs(transport_errors) do
+    if lower:find(pattern, 1, true) then return true end
+  end
+  return false
+end
+
+---@param args string[]
+---@return boolean ok
+---@return string output
+local function run_remote(args)
+  local output = util.system(remote_cmd(args))
+  return vim.v.shell_error == 0, vim.trim(output)
+end
+
+---Run a kitty remote control command
+---@param args string[]
+---@return boolean ok
+M.remote = function(args)
+  local ok, output = run_remote(args)
+  if not ok then
+    require('nekifoch.notify').warn('kitty remote control failed: ' .. output)
+    return false
+  end
+  return true
+end
+
+---Show `family` without touching kitty.conf, needs remote control
+---@param family string
+---@return boolean ok
+M.preview_font = function(family)
+  if strategy() ~= 'remote' then return false end
+  require('nekifoch.log').debug('preview ' .. family)
+  -- Don't block cursor movement in the picker while kitty re-renders
+  return vim.fn.jobstart(
+    remote_cmd({ 'load-config', '--override', 'font_family=' .. family })
+  ) > 0
+end
+
+---Drop the preview override and return to the configured font
+M.cancel_preview = function()
+  if strategy() == 'remote' then
+    vim.fn.jobstart(remote_cmd({ 'load-config' }))
+  end
+end
+
+-- The skipped reload is reported once per session
+local told_outside = false
+
+M.reload = function(change)
+  if strategy() == 'remote' then
+    local args = { 'load-config' }
+    -- A size alone applies instantly without re-reading the whole config
+    if
+      change
+      and tonumber(change.size)
+      and not change.font
+      and not change.theme
+    then
+      args = { 'set-font-size', '--all', '--', tostring(change.size) }
+    end
+    if vim.fn.executable('kitten') == 0 and vim.fn.executable('kitty') == 0 then
+      require('nekifoch.notify').warn(
+        'kitten not found, reload kitty with ctrl+shift+F5'
+      )
+      return
+    end
+    -- Only remote control reports whether kitty accepted the change
+    require('nekifoch.log').info('reload kitty: ' .. args[1])
+    local ok, output = run_remote(args)
+    if ok then return true end
+    -- The config is fine as far as anyone knows, keep it
+    if transport_error(output) then
+      require('nekifoch.notify').warn(
+        'kitty remote control failed, the config is written but kitty was '
+          .. 'not reloaded: '
+          .. output
+      )
+      return
+    end
+    return false, output
+  end
+
+  -- No Unix signals on Windows, kitty there only listens for remote control
+  if util.is_windows() then return end
+
+  -- Signalling kitty instances Neovim doesn't run in would apply the change
+  -- where it wasn't meant to, unless `reload_all` asks for every instance
+  if not M.inside() and not require('nekifoch').config.reload_all then
+    require('nekifoch.log').info('not inside kitty, not reloaded')
+    if not told_outside then
+      told_outside = true
+      require('nekifoch.notify').info(
+        'Not running inside kitty, kitty.conf is written without a reload'
+      )
+    end
+    return
+  end
+
+  -- kitty exports its PID to child processes, only reload the instance
+  -- hosting this Neovim unless asked to reach every instance
+  local pids = { vim.env.KITTY_PID }
+  if require('nekifoch').config.reload_all or not vim.env.KITTY_PID then
+    pids = M.pids()
+  end
+  -- The config is already written, a failed reload is only worth a warning
+  if not pids then
+    require('nekifoch.log').warn('pidof/pgrep not found, kitty not reloaded')
+    require('nekifoch.notify').warn(
+      'pidof/pgrep not found, reload kitty with ctrl+shift+F5'
+    )
+    return
+  end
+  if #pids > 0 then
+    require('nekifoch.log').info('reload kitty ' .. table.concat(pids, ' '))
+    util.system(vim.list_extend({ 'kill', '-USR1' }, pids))
+  end
+end
+
+---PIDs of running kitty instances, `nil` when they can't be looked up.
+---macOS has no `pidof`, `pgrep` is used there
+---@return string[]|nil
+M.pids = function()
+  local output
+  if vim.fn.executable('pidof') == 1 then
+    output = util.system({ 'pidof', 'kitty' })
+  elseif vim.fn.executable('pgrep') == 1 then
+    output = util.system({ 'pgrep', '-x', 'kitty' })
+  else
+    return nil
+  end
+  return vim.split(vim.trim(output), '%s+', { trimempty = true })
+end
+
+return M
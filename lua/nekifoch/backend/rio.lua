@@ -0,0 +1,112 @@
+local util = require('nekifoch.utils')
+local toml = require('nekifoch.toml')
+
+---@type NekifochBackend
+local M = {
+  name = 'rio',
+  kitty_fonts = false,
+}
+
+-- Rio's tables for the font styles
+local tables = {
+  normal = 'regular',
+  bold = 'bold',
+  italic = 'italic',
+  bold_italic = 'bold-italic',
+}
+
+---@param style string `normal`, `bold`, `italic` or `bold_italic`
+---@return NekifochTomlKey[]
+local function family_keys(style)
+  local name = tables[style]
+  local pattern = name:gsub('%-', '%%-')
+  local keys = {
+    { 'fonts.' .. name, '^%s*family%s*=', 'family' },
+    { 'fonts', '^%s*' .. pattern .. '%.family%s*=', pattern .. '%.family' },
+    { 'fonts', '^%s*' .. pattern .. '%s*=%s*{.*family%s*=', 'family' },
+    {
+      '',
+      '^%s*fonts%.' .. pattern .. '%.family%s*=',
+      'fonts%.' .. pattern .. '%.family',
+    },
+  }
+  -- `fonts.family` sets every style at once
+  if style == 'normal' then
+    table.insert(keys, { 'fonts', '^%s*family%s*=', 'family' })
+  end
+  return keys
+end
+
+local size_keys = {
+  { 'fonts', '^%s*size%s*=', 'size' },
+  { '', '^%s*fonts%.size%s*=', 'fonts%.size' },
+}
+
+M.config_path = function() return require('nekifoch').config.rio_conf_path end
+
+---@return string[]
+local function read_lines()
+  return vim.split(util.read_file(M.config_path()), '\n')
+end
+
+---@param lines string[]
+local function write_lines(lines)
+  util.write_file(M.config_path(), table.concat(lines, '\n'))
+end
+
+M.get = function()
+  local lines = read_lines()
+  local font = {}
+
+  for _, style in ipairs({ 'normal', 'bold', 'italic', 'bold_italic' }) do
+    local index, key = toml.locate(lines, family_keys(style))
+    if index then
+      font[style == 'normal' and 'font' or style] =
+        toml.string(lines[index], key)
+    end
+  end
+
+  local index, key = toml.locate(lines, size_keys)
+  if index then
+    font.size = lines[index]:match(key .. '%s*=%s*([%d%.]+)')
+  end
+
+  return font
+end
+
+M.set_font = function(family, style)
+  style = style or 'normal'
+  local lines = read_lines()
+  local value = '"' .. family .. '"'
+  local action = toml.set_value(
+    lines,
+    family_keys(style),
+    'fonts.' .. tables[style],
+    'family = ' .. value,
+    value
+  )
+  write_lines(lines)
+  return action
+end
+
+M.set_size = function(size)
+  local lines = read_lines()
+  local value = toml.format_size(size)
+  local action =
+    toml.set_value(lines, size_keys, 'fonts', 'size = ' .. value, value)
+  write_lines(lines)
+  return action
+end
+
+M.unset_size = function()
+  local lines = read_lines()
+  local index = toml.locate(lines, size_keys)
+  if not index then return end
+  table.remove(lines, index)
+  write_lines(lines)
+end
+
+-- Rio watches its configuration file
+M.reload = function(_) end
+
+return M
@@ -0,0 +1,161 @@
+local util = require('nekifoch.utils')
+
+---@type NekifochBackend
+local M = {
+  name = 'windows_terminal',
+  kitty_fonts = false,
+}
+
+M.config_path = function()
+  return require('nekifoch').config.windows_terminal_conf_path
+end
+
+---Find the object value of `"key"` between `from` and `to`, skipping braces
+---inside strings
+---@param content string
+---@param key string
+---@param from integer|nil
+---@param to integer|nil
+---@return integer|nil open Position of `{`
+---@return integer|nil close Position of the matching `}`
+local function object(content, key, from, to)
+  local _, open = content:find('"' .. key .. '"%s*:%s*{', from)
+  if not open or (to and open > to) then return nil end
+
+  local depth, in_string, i = 0, false, open
+  while i <= #content do
+    local c = content:sub(i, i)
+    if in_string then
+      if c == '\\' then
+        i = i + 1
+      elseif c == '"' then
+        in_string = false
+      end
+    elseif c == '"' then
+      in_string = true
+    elseif c == '{' then
+      depth = depth + 1
+    elseif c == '}' then
+      depth = depth - 1
+      if depth == 0 then return open, i end
+    end
+    i = i + 1
+  end
+end
+
+---Range of `profiles.defaults.font`, the font every profile inherits
+---@param content string
+---@return integer defaults_open
+---@return integer defaults_close
+---@return integer|nil font_open
+---@return integer|nil font_close
+local function font_object(content)
+  local profiles_open, profiles_close = object(content, 'profiles')
+  local defaults_open, defaults_close =
+    object(content, 'defaults', profiles_open, profiles_close)
+  if not defaults_open then
+    error('profiles.defaults not found in ' .. M.config_path(), 0)
+  end
+  return defaults_open,
+    defaults_close,
+    object(content, 'font', defaults_open, defaults_close)
+end
+
+---@param content string
+---@param open integer
+---@param close integer
+---@return boolean
+local function is_empty(content, open, close)
+  return content:sub(open + 1, close - 1):match('^%s*$') ~= nil
+end
+
+---Set `"key": value` in the default profile font, keeping the rest of the
+---file as written
+---@param key string
+---@param value string JSON encoded value
+---@return NekifochEditAction
+local function set_font_value(key, value)
+  local path = M.config_path()
+  local content = util.read_file(path)
+  local defaults_open, defaults_close, font_open, font_close =
+    font_object(content)
+
+  local action, updated = 'appended', nil
+  if font_open then
+    local _, eq = content:find('"' .. key .. '"%s*:%s*', font_open)
+    if eq and eq < font_close then
+      local rest = content:sub(eq + 1)
+      local token = rest:match('^"[^"]*"') or rest:match('^[%d%.]+') or ''
+      updated = content:sub(1, eq) .. value .. rest:sub(#token + 1)
+      action = 'replaced'
+    else
+      local comma = is_empty(content, font_open, font_close) and '' or ','
+      updated = content:sub(1, font_open)
+        .. ' "'
+        .. key
+        .. '": '
+        .. value
+        .. comma
+        .. content:sub(font_open + 1)
+    end
+  else
+    local comma = is_empty(content, defaults_open, defaults_close) and ''
+      or ','
+    updated = content:sub(1, defaults_open)
+      .. ' "font": { "'
+      .. key
+      .. '": '
+      .. value
+      .. ' }'
+      .. comma
+      .. content:sub(defaults_open + 1)
+  end
+
+  util.write_file(path, updated)
+  return action
+end
+
+M.get = function()
+  local content = util.read_file(M.config_path())
+  local _, _, font_open, font_close = font_object(content)
+  if not font_open then return {} end
+
+  local font = content:sub(font_open, font_close)
+  return {
+    font = font:match('"face"%s*:%s*"([^"]*)"'),
+    size = font:match('"size"%s*:%s*([%d%.]+)'),
+  }
+end
+
+M.set_font = function(family, style)
+  if style then
+    error('Windows Terminal has no separate ' .. style .. ' font', 0)
+  end
+  return set_font_value('face', vim.json.encode(family))
+end
+
+M.set_size = function(size) return set_font_value('size', tostring(size)) end
+
+M.unset_size = function()
+  local path = M.config_path()
+  local content = util.read_file(path)
+  local _, _, font_open, font_close = font_object(content)
+  if not font_open then return end
+
+  local font = content:sub(font_open, font_close)
+  -- Take the comma separating it from a neighbouring key along
+  local removed, count = font:gsub(',%s*"size"%s*:%s*[%d%.]+', '', 1)
+  if count == 0 then
+    removed, count = font:gsub('"size"%s*:%s*[%d%.]+%s*,?%s*', '', 1)
+  end
+  if count == 0 then return end
+  util.write_file(
+    path,
+    content:sub(1, font_open - 1) .. removed .. content:sub(font_close + 1)
+  )
+end
+
+-- Windows Terminal reloads settings.json as soon as it changes
+M.reload = function(_) end
+
+return M
@@ -0,0 +1,113 @@
+local M = {}
+
+---@class NekifochBackup
+---@field file string Backed up config file
+---@field path string Backup copy
+---@field stamp string
+
+---@return NekifochBackupConfig
+local function config() return require('nekifoch').config.backup end
+
+---Backups encode the original path in their name like 'undodir' does,
+---separators as `%` and a Windows drive colon as `%;`
+---@param file string
+---@return string
+local function prefix(file)
+  local path = vim.fn.fnamemodify(file, ':p'):gsub('\\', '/')
+  return config().dir .. '/' .. path:gsub(':', '%%;'):gsub('/', '%%')
+end
+
+---@param name string Backup name without the stamp
+---@return string
+local function decode(name)
+  return (name:gsub('%%;', ':'):gsub('%%', '/'))
+end
+
+---@param file string|nil Only backups of `file`
+---@return NekifochBackup[] Oldest first
+M.list = function(file)
+  local pattern = file and prefix(file) or (config().dir .. '/')
+  local backups = {}
+  for _, path in ipairs(vim.fn.glob(pattern .. '*.bak', true, true)) do
+    local name, stamp =
+      vim.fn.fnamemodify(path, ':t'):match('^(.*)%.(%d+%-%d+)%.bak$')
+    if name then
+      table.insert(backups, {
+        file = decode(name),
+        path = path,
+        stamp = stamp,
+      })
+    end
+  end
+  table.sort(backups, function(a, b) return a.stamp < b.stamp end)
+  return backups
+end
+
+---@return string
+local function new_stamp()
+  -- Sub-second suffix keeps consecutive writes from clobbering each other
+  local seconds, micro = vim.loop.gettimeofday()
+  return os.date('%Y%m%d%H%M%S', seconds) .. '-' .. string.format('%06d', micro)
+end
+
+---Stamp shared by the backups made inside `M.group`
+---@type string|nil
+local group_stamp = nil
+
+---Run `fn` giving every backup it makes the same stamp, so `restore` undoes
+---a change to several files at once
+---@generic T
+---@param fn fun(): T
+---@return T
+M.group = function(fn)
+  if group_stamp then return fn() end
+  group_stamp = new_stamp()
+  local ok, result = pcall(fn)
+  group_stamp = nil
+  if not ok then error(result, 0) end
+  return result
+end
+
+---Copy `file` into the backup directory and drop the oldest copies
+---@param file string
+M.create = function(file)
+  if not config().enable or vim.fn.filereadable(file) == 0 then return end
+  vim.fn.mkdir(config().dir, 'p')
+
+  local path = prefix(file) .. '.' .. (group_stamp or new_stamp()) .. '.bak'
+  -- Written twice in one group, the copy from before the first write counts
+  if vim.fn.filereadable(path) == 1 then return end
+  vim.fn.writefile(vim.fn.readfile(file, 'b'), path, 'b')
+
+  local backups = M.list(file)
+  for i = 1, #backups - config().keep do
+    os.remove(backups[i].path)
+  end
+end
+
+---Put the most recent backups back in place and forget them. A change that
+---wrote several files left backups with the same stamp, all are restored
+---@return NekifochBackup[] restored Empty without backups
+M.restore = function()
+  local backups = M.list()
+  local last = backups[#backups]
+  if not last then return {} end
+
+  local util = require('nekifoch.utils')
+  local restored = {}
+  for _, backup in ipairs(backups) do
+    if backup.stamp == last.stamp then
+      vim.fn.writefile(
+        vim.fn.readfile(backup.path, 'b'),
+        util.resolve(backup.file),
+        'b'
+      )
+      os.remove(backup.path)
+      table.insert(restored, backup)
+    end
+  end
+  util.invalidate()
+  return restored
+end
+
+return M
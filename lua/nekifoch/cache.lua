@@ -0,0 +1,237 @@
+local util = require('nekifoch.utils')
+
+local M = {}
+
+---@class NekifochFontCache
+---@field formatted table<string, string> Font name without spaces -> family
+---@field list string[] Font families usable by the terminal backend
+
+---@type NekifochFontCache|nil
+M.fonts = nil
+
+---Fonts persisted between sessions
+---@return string
+M.file = function() return vim.fn.stdpath('cache') .. '/nekifoch/fonts.json' end
+
+---Directories whose changes mean fonts were installed or removed, the
+---fontconfig cache is updated by `fc-cache`
+---@return string[]
+M.font_dirs = function()
+  local home = vim.loop.os_homedir()
+  local xdg_data = vim.env.XDG_DATA_HOME or home .. '/.local/share'
+  local xdg_cache = vim.env.XDG_CACHE_HOME or home .. '/.cache'
+  if util.is_windows() then
+    return {
+      (vim.env.LOCALAPPDATA or '') .. '/Microsoft/Windows/Fonts',
+      (vim.env.WINDIR or 'C:/Windows') .. '/Fonts',
+    }
+  end
+  local dirs = {
+    xdg_data .. '/fonts',
+    home .. '/.fonts',
+    xdg_cache .. '/fontconfig',
+    '/usr/share/fonts',
+    '/usr/local/share/fonts',
+  }
+  if util.is_mac() then
+    vim.list_extend(dirs, { home .. '/Library/Fonts', '/Library/Fonts' })
+  end
+  return dirs
+end
+
+---Whether a font directory changed after `time`
+---@param time integer
+---@return boolean
+local function fonts_changed_since(time)
+  for _, dir in ipairs(M.font_dirs()) do
+    local stat = vim.loop.fs_stat(dir)
+    if stat and stat.mtime.sec > time then return true end
+  end
+  return false
+end
+
+---How the font list is made: the fonts `kitty +list-fonts` takes, the
+---monospace ones when kitty.conf is edited from outside kitty, or all
+---@return "'kitty'"|"'monospace'"|"'all'"
+local function source()
+  if not require('nekifoch.backend').current().kitty_fonts then return 'all' end
+  return require('nekifoch.backend.kitty').inside() and 'kitty' or 'monospace'
+end
+
+---Fonts the backend takes, through its `list_filter` when it has one. The
+---cache keeps every font so switching backends doesn't stale it
+---@param formatted table<string, string>
+---@param list string[]
+---@return table<string, string> formatted
+---@return string[] list
+local function usable(formatted, list)
+  local filter = require('nekifoch.backend').current().list_filter
+  if not filter then return formatted, list end
+  list = filter(list)
+  local kept = {}
+  for _, family in ipairs(list) do
+    kept[family] = true
+  end
+  -- Names resolved by set_font and completion must be in the list too
+  local filtered = {}
+  for name, family in pairs(formatted) do
+    if kept[family] then filtered[name] = family end
+  end
+  return filtered, list
+end
+
+---Load fonts saved by an earlier session unless they are older than the TTL
+---or were filtered for another kind of terminal
+---@return NekifochFontCache|nil
+local function load()
+  local ttl = require('nekifoch').config.cache.ttl
+  local stat = vim.loop.fs_stat(M.file())
+  if ttl <= 0 or not stat or os.time() - stat.mtime.sec > ttl then
+    return nil
+  end
+  if fonts_changed_since(stat.mtime.sec) then return nil end
+
+  local ok, saved = pcall(vim.json.decode, util.read_file(M.file()))
+  if not ok or type(saved) ~= 'table' or saved.source ~= source() then
+    return nil
+  end
+  return { formatted = saved.formatted, list = saved.list }
+end
+
+---@param fonts NekifochFontCache
+local function save(fonts)
+  if require('nekifoch').config.cache.ttl <= 0 then return end
+  vim.fn.mkdir(vim.fn.fnamemodify(M.file(), ':h'), 'p')
+  vim.fn.writefile({
+    vim.json.encode({
+      source = source(),
+      formatted = fonts.formatted,
+      list = fonts.list,
+    }),
+  }, M.file())
+end
+
+---Keep enumerated fonts in memory and on disk. An empty list means the
+---listing command failed, it is not kept so the next call tries again
+---@param formatted table<string, string>
+---@param list string[]
+local function keep(formatted, list)
+  if #list == 0 then return end
+  M.fonts = { formatted = formatted, list = list }
+  save(M.fonts)
+end
+
+---Get fonts usable by the terminal, enumerating them only when the on-disk
+---cache is stale
+---@return table<string, string> formatted
+---@return string[] list
+M.get = function()
+  if not M.fonts then M.fonts = load() end
+  if not M.fonts then
+    local formatted, list
+    local kind = source()
+    if kind == 'kitty' then
+      formatted, list = util.listKittyCompatibleFonts()
+    else
+      local installed = util.listInstalledFonts(kind == 'monospace')
+      formatted, list = util.formatFonts(installed)
+    end
+    keep(formatted, list)
+    if not M.fonts then return usable(formatted, list) end
+  end
+  return usable(M.fonts.formatted, M.fonts.list)
+end
+
+---Whether fonts are available without enumerating them
+---@return boolean
+M.ready = function()
+  if not M.fonts then M.fonts = load() end
+  return M.fonts ~= nil
+end
+
+M.loading = false
+
+---@type fun(formatted: table<string, string>, list: string[])[]
+M.waiting = {}
+
+---Enumerate fonts in the background without blocking the editor,
+---`callback` runs once fonts are cached
+---@param callback fun(formatted: table<string, string>, list: string[])|nil
+M.get_async = function(callback)
+  if M.ready() then
+    if callback then callback(usable(M.fonts.formatted, M.fonts.list)) end
+    return
+  end
+
+  if callback then table.insert(M.waiting, callback) end
+  if M.loading then return end
+  M.loading = true
+
+  local function done(formatted, list)
+    keep(formatted, list)
+    -- The font window tags Nerd Fonts by their glyphs, look them up too
+    require('nekifoch.nerd').probe_async()
+    M.loading = false
+    local waiting = M.waiting
+    M.waiting = {}
+    for _, cb in ipairs(waiting) do
+      cb(usable(formatted, list))
+    end
+  end
+
+  local kind = source()
+  if kind == 'kitty' then
+    util.listKittyCompatibleFontsAsync(done)
+  else
+    util.listInstalledFontsAsync(
+      function(installed) done(util.formatFonts(installed)) end,
+      kind == 'monospace'
+    )
+  end
+end
+
+---Forget cached fonts, in memory and on disk
+M.invalidate = function()
+  M.fonts = nil
+  require('nekifoch.nerd').reset()
+  os.remove(M.file())
+end
+
+---Drop cached fonts and enumerate them again
+---@return table<string, string> formatted
+---@return string[] list
+M.refresh = function()
+  M.invalidate()
+  return M.get()
+end
+
+---@type uv_fs_event_t[]
+M.watchers = {}
+
+---Re-enumerate fonts in the background when a font directory changes, so
+---pickers and completion pick up new fonts
+M.watch = function()
+  if #M.watchers > 0 then return end
+  local pending = false
+  local function on_change()
+    -- Installing a font touches many files, rebuild once
+    if pending then return end
+    pending = true
+    vim.defer_fn(function()
+      pending = false
+      M.invalidate()
+      M.get_async()
+    end, 1000)
+  end
+
+  for _, dir in ipairs(M.font_dirs()) do
+    if vim.fn.isdirectory(dir) == 1 then
+      local watcher = vim.loop.new_fs_event()
+      if watcher and watcher:start(dir, {}, vim.schedule_wrap(on_change)) then
+        table.insert(M.watchers, watcher)
+      end
+    end
+  end
+end
+
+return M
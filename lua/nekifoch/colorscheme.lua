@@ -0,0 +1,27 @@
+-- Fonts paired with colorschemes, applied whenever one is loaded
+local M = {}
+
+---Apply the profile paired with `name` where it differs from the config
+---@param name string|nil Colorscheme, the current one when `nil`
+M.apply = function(name)
+  name = name or vim.g.colors_name
+  local profile = name and require('nekifoch').config.by_colorscheme[name]
+  if not profile then return end
+
+  require('nekifoch.log').info('colorscheme ' .. name)
+  local _, err = require('nekifoch.command_func').update_profile(profile)
+  if err then require('nekifoch.notify').error(err) end
+end
+
+M.start = function()
+  local group =
+    vim.api.nvim_create_augroup('NekifochColorscheme', { clear = true })
+  vim.api.nvim_create_autocmd('ColorScheme', {
+    group = group,
+    callback = function(args) M.apply(args.match) end,
+  })
+  -- The colorscheme may be set before the plugin loads
+  vim.schedule(function() M.apply() end)
+end
+
+return M
@@ -1,19 +1,17 @@
 -- TODO: remake all
--- FIX: autocompletion after entered font and second argument
 
-local util = require('nekifoch.utils')
+local cache = require('nekifoch.cache')
 local func = require('nekifoch.command_func')
+local notify = require('nekifoch.notify')
 
 local M = {}
 
 function M.setup()
   vim.api.nvim_create_user_command('Nekifoch', function(opts)
-    local cmd = vim.trim(opts.args):match('^(%S+)') -- Извлекаем первое слово как команду
-    if M.commands[cmd] then
-      M.commands[cmd](opts)
-    else
-      print('Command not found')
-    end
+    -- A window opened from the command line has nothing to go back to,
+    -- even when an earlier one was left by switching windows
+    require('nekifoch.ui').reset()
+    M.run(opts.fargs[1], vim.list_slice(opts.fargs, 2))
   end, {
     nargs = '*',
     desc = 'Replace font family in Kitty configuration file',
@@ -21,64 +19,523 @@ function M.setup()
   })
 end
 
+--- Execute a subcommand and return its structured result
+---@param cmd string|nil Subcommand name
+---@param args string[] Arguments following the subcommand
+---@return any result
+---@return string|nil err
+function M.execute(cmd, args)
+  if not (cmd and M.commands[cmd]) then return nil, 'Command not found' end
+  local ok, result, err = pcall(M.commands[cmd], args or {})
+  if not ok then err, result = result, nil end
+  if err then
+    require('nekifoch.log').error(
+      table.concat(vim.list_extend({ cmd }, args or {}), ' ') .. ': ' .. err
+    )
+  end
+  return result, err
+end
+
+--- Dispatch a subcommand and report its result to the user
+---@param cmd string|nil Subcommand name
+---@param args string[] Arguments following the subcommand
+---@return any result
+---@return string|nil err
+function M.run(cmd, args)
+  local result, err = M.execute(cmd, args)
+  if err then
+    notify.error(err)
+  elseif result ~= nil and M.formatters[cmd] then
+    M.formatters[cmd](result)
+  end
+  return result, err
+end
+
 M.commands = {
-  list = function() func.list() end,
-  check = function() func.check() end,
-  set_font = function(opts) func.set_font(opts) end,
-  set_size = function(opts) func.set_size(opts) end,
+  list = function(args) return func.list(args) end,
+  check = function(args) return func.check(args) end,
+  set_font = function(args) return func.set_font(args) end,
+  set_bold_font = function(args) return func.set_font(args, 'bold') end,
+  set_italic_font = function(args) return func.set_font(args, 'italic') end,
+  set_bold_italic_font = function(args)
+    return func.set_font(args, 'bold_italic')
+  end,
+  set_size = function(args) return func.set_size(args) end,
+  apply = function(args) return func.apply_font(args) end,
+  size_up = function(args) return func.size_up(args) end,
+  size_down = function(args) return func.size_down(args) end,
+  ladder = function(args) return func.ladder(args) end,
+  font_info = function(args) return func.font_info(args) end,
+  coverage = function(args) return func.coverage(args) end,
+  detect = function(args) return func.detect(args) end,
+  which = function(args) return func.which(args) end,
+  restore = function(args) return func.restore(args) end,
+  profile = function(args) return func.profile(args) end,
+  bundle = function(args) return func.bundle(args) end,
+  host = function(args) return func.host(args) end,
+  dpi = function(args) return func.dpi(args) end,
+  yank = function(args) return func.yank(args) end,
+  export = function(args) return func.export(args) end,
+  import = function(args) return func.import(args) end,
+  menu = function(args) return func.menu(args) end,
+  wizard = function(args) return func.wizard(args) end,
+  help = function(args) return func.help(args) end,
+  log = function(args) return func.log(args) end,
+  favorites = function(args) return func.favorites(args) end,
+  recent = function(args) return func.recent(args) end,
+  next_font = function(args) return func.next_font(args) end,
+  prev_font = function(args) return func.prev_font(args) end,
+  features = function(args) return func.features(args) end,
+  modify = function(args) return func.modify(args) end,
+  set = function(args) return func.set(args) end,
+  install = function(args) return func.install(args) end,
+  fc_cache = function(args) return func.fc_cache(args) end,
+  refresh_cache = function(args) return func.refresh_cache(args) end,
+  refresh = function(args) return func.refresh_cache(args) end,
 }
 
-function M.complete(findstart)
-  if findstart == 1 then
-    return vim.fn.col('.') - 1
-  else
-    local args = vim.fn.split(vim.fn.getcmdline(), ' ')
-    local second_arg = args[2]
-    if second_arg == 'set_font' then
-      return M.handleSetFontComplete(args)
-    elseif
-      second_arg == 'list'
-      or second_arg == 'set_size'
-      or second_arg == 'check'
+---@param info NekifochFontInfo
+---@return string[]
+function M.font_info_lines(info)
+  local function yes(value) return value and 'yes' or 'no' end
+  local lines = {
+    info.family,
+    ' styles: ' .. table.concat(info.styles, ', '),
+    ' monospace: ' .. yes(info.monospace),
+    ' nerd glyphs: ' .. yes(info.nerd),
+    ' ligatures: ' .. yes(info.ligatures),
+    ' kitty compatible: ' .. yes(info.kitty_compatible),
+    ' faces:',
+  }
+  for _, face in ipairs(info.faces) do
+    table.insert(
+      lines,
+      '  - ' .. face.style .. ' (' .. face.psname .. ', ' .. face.format .. ')'
+    )
+    table.insert(lines, '    ' .. vim.fn.fnamemodify(face.file, ':~'))
+  end
+  return lines
+end
+
+---Tell the user when a setting had to be added to the config file
+---@param change NekifochFont
+local function report_change(change)
+  if change.action and change.action ~= 'replaced' then
+    notify.warn(
+      'Setting was missing, '
+        .. change.action
+        .. ' it in '
+        .. require('nekifoch.backend').current().config_path()
+    )
+  end
+end
+
+M.formatters = {
+  set_font = report_change,
+  set_bold_font = report_change,
+  set_italic_font = report_change,
+  set_bold_italic_font = report_change,
+  set_size = report_change,
+  apply = report_change,
+  bundle = report_change,
+  size_up = report_change,
+  size_down = report_change,
+  ---@param change NekifochFont
+  next_font = function(change)
+    report_change(change)
+    notify.info('Font family: ' .. change.font)
+  end,
+  ---@param change NekifochFont
+  prev_font = function(change)
+    report_change(change)
+    notify.info('Font family: ' .. change.font)
+  end,
+  ---@param result NekifochFont|NekifochOptionValue
+  set = function(result)
+    if result.option then
+      return notify.info(result.option .. ': ' .. (result.value or '(default)'))
+    end
+    report_change(result)
+    for option, value in pairs(result.options) do
+      notify.info(option .. ': ' .. value)
+    end
+  end,
+  ---@param change NekifochFont|nil `nil` when a window was opened
+  modify = function(change)
+    if not change then return end
+    report_change(change)
+    for what, value in pairs(change.modify) do
+      notify.info('modify_font ' .. what .. ': ' .. value)
+    end
+  end,
+  ---@param change NekifochFont|nil `nil` when the window was opened
+  features = function(change)
+    if not change then return end
+    report_change(change)
+    local text = change.spec.features
+    notify.info('Font features: ' .. (text ~= '' and text or 'none'))
+  end,
+  ---@param fonts string[]
+  list = function(fonts) require('nekifoch.picker').list(fonts) end,
+  ---@param current NekifochFont
+  check = function(current)
+    local styles = ''
+    for _, style in ipairs(require('nekifoch.utils').styles) do
+      if current[style] then
+        styles = styles .. '\n\t' .. style .. ': ' .. current[style]
+      end
+    end
+    local keys = vim.tbl_keys(current.spec or {})
+    table.sort(keys)
+    for _, key in ipairs(keys) do
+      styles = styles .. '\n\t' .. key .. ': ' .. current.spec[key]
+    end
+    notify.info(
+      current.font .. '\n\tFont size: ' .. tostring(current.size) .. styles,
+      'Current font'
+    )
+  end,
+  ---@param result { dpi: number, size: number|nil }
+  dpi = function(result)
+    notify.info(
+      string.format('%.0f dpi', result.dpi)
+        .. (result.size and (', font size ' .. result.size) or '')
+    )
+  end,
+  ---@param lines string[]
+  yank = function(lines)
+    notify.info(lines, 'Copied to the clipboard')
+  end,
+  ---@param path string
+  export = function(path) notify.info('Settings exported to ' .. path) end,
+  ---@param result { path: string, change: NekifochFont|nil }
+  import = function(result)
+    notify.info('Settings imported from ' .. result.path)
+    if result.change then report_change(result.change) end
+  end,
+  ---@param host NekifochHost
+  host = function(host)
+    local lines = { host.name .. (host.action and (' ' .. host.action) or '') }
+    if not host.profile then
+      table.insert(lines, ' no profile for this host')
+    end
+    local fields = vim.tbl_keys(host.profile or {})
+    table.sort(fields)
+    for _, field in ipairs(fields) do
+      table.insert(lines, ' ' .. field .. ': ' .. host.profile[field])
+    end
+    notify.info(lines)
+  end,
+  ---@param info NekifochFontInfo
+  font_info = function(info)
+    notify.info(M.font_info_lines(info))
+  end,
+  ---@param coverage NekifochCoverage
+  coverage = function(coverage)
+    if #coverage.missing == 0 then
+      return notify.info(
+        coverage.font .. ' has glyphs for all of ' .. coverage.text
+      )
+    end
+    local codes = vim.tbl_map(
+      function(char) return string.format('U+%04X', vim.fn.char2nr(char)) end,
+      coverage.missing
+    )
+    notify.warn(
+      coverage.font
+        .. ' has no glyphs for '
+        .. table.concat(coverage.missing, ' ')
+        .. ' ('
+        .. table.concat(codes, ', ')
+        .. ')'
+    )
+  end,
+  ---@param directives NekifochDirective[]
+  which = function(directives)
+    local lines = {}
+    for _, directive in ipairs(directives) do
+      table.insert(
+        lines,
+        directive.option
+          .. ' '
+          .. directive.value
+          .. '  '
+          .. vim.fn.fnamemodify(directive.file, ':~')
+          .. ':'
+          .. directive.line
+      )
+    end
+    notify.info(lines)
+  end,
+  ---@param fonts string[]
+  refresh_cache = function(fonts)
+    notify.info('Font cache rebuilt: ' .. #fonts .. ' fonts')
+  end,
+  ---@param fonts string[]
+  refresh = function(fonts)
+    notify.info('Font cache rebuilt: ' .. #fonts .. ' fonts')
+  end,
+  ---@param restored NekifochBackup[]
+  restore = function(restored)
+    notify.info(vim.tbl_map(
+      function(backup)
+        return 'Restored ' .. backup.file .. ' from ' .. backup.path
+      end,
+      restored
+    ))
+  end,
+  ---@param detection NekifochDetection
+  detect = function(detection)
+    local lines = {
+      'Detected terminal: ' .. (detection.terminal or 'unknown'),
+      'Backend: ' .. detection.backend,
+    }
+    if detection.kitty_version then
+      table.insert(
+        lines,
+        'kitty ' .. table.concat(detection.kitty_version, '.')
+      )
+    end
+    table.insert(
+      lines,
+      'Config: '
+        .. detection.config_path
+        .. (detection.exists and '' or ' (not found)')
+    )
+    for _, name in ipairs(require('nekifoch.detect').env_vars) do
+      table.insert(lines, ' $' .. name .. ' = ' .. (detection.env[name] or ''))
+    end
+    for _, diagnostic in ipairs(detection.diagnostics) do
+      table.insert(
+        lines,
+        vim.fn.fnamemodify(diagnostic.file, ':~')
+          .. ':'
+          .. diagnostic.line
+          .. ': '
+          .. diagnostic.message
+      )
+    end
+    notify.info(lines, 'Nekifoch detect')
+  end,
+}
+
+-- Taken from `M.commands` so completion can't miss a subcommand
+M.subcommands = vim.tbl_keys(M.commands)
+table.sort(M.subcommands)
+
+---Complete a font family name that may span several words
+---@param typed string Everything typed after the subcommand
+---@param arglead string Word under the cursor
+---@return string[]
+function M.complete_font(typed, arglead)
+  if not cache.ready() then
+    -- Don't freeze the command line, offer fonts once they are loaded
+    cache.get_async()
+    return {}
+  end
+  local _, fonts = cache.get()
+  -- Words before the one being completed are already on the command line
+  local done = typed:sub(1, #typed - #arglead):gsub('^["\']', ''):lower()
+
+  if done == '' then
+    local needle = (arglead:gsub('^["\']', ''):gsub('%s+', ''))
+    local sorted = vim.deepcopy(fonts or {})
+    table.sort(sorted)
+    if needle == '' then return require('nekifoch.favorites').first(sorted) end
+    -- Subsequences rank by how many letters are adjacent or start words,
+    -- so `jbm` offers JetBrainsMono first
+    return vim.fn.matchfuzzy(sorted, needle)
+  end
+
+  local matches = {}
+  for _, font in ipairs(fonts or {}) do
+    local lower = font:lower()
+    if
+      lower:sub(1, #done) == done
+      and vim.startswith(lower:sub(#done + 1), arglead:lower())
     then
-      return {}
-    else
-      return { 'check', 'set_font', 'set_size', 'list' }
+      table.insert(matches, font:sub(#done + 1))
     end
   end
+  table.sort(matches)
+  return matches
 end
 
-function M.handleSetFontComplete(args)
-  if args[3] then
-    local lineUntilCursor = vim.fn.getcmdline():sub(1, vim.fn.col('.') - 1)
-    if lineUntilCursor:sub(-1) == ' ' then return {} end
-    local partialFontName = table.concat(args, ' ', 3)
-    local formattedFonts, _ =
-      util.compareFontsWithKittyListFonts(util.getCachedInstalledFonts())
-    if formattedFonts then
-      local keys = vim.tbl_keys(formattedFonts)
-      local filteredKeys = {}
-      for _, key in ipairs(keys) do
-        if key:lower():find(vim.pesc(partialFontName:lower()), 1, true) then
-          table.insert(filteredKeys, key)
-        end
-      end
-      table.sort(filteredKeys)
-      return filteredKeys
-    else
-      return {}
+---Style fonts also accept `auto`
+---@param typed string
+---@param arglead string
+---@return string[]
+function M.complete_style_font(typed, arglead)
+  local matches = M.complete_font(typed, arglead)
+  if typed == arglead and vim.startswith('auto', arglead) then
+    table.insert(matches, 1, 'auto')
+  end
+  return matches
+end
+
+---@param _ string
+---@param arglead string
+---@return string[]
+function M.complete_font_option(_, arglead)
+  return vim.tbl_filter(
+    function(option) return vim.startswith(option, arglead) end,
+    func.font_options
+  )
+end
+
+---@param _ string
+---@param arglead string
+---@return string[]
+function M.complete_profile(_, arglead)
+  local names = vim.tbl_filter(
+    function(name) return vim.startswith(name, arglead) end,
+    vim.tbl_keys(require('nekifoch').config.profiles)
+  )
+  table.sort(names)
+  return names
+end
+
+---@param _ string
+---@param arglead string
+---@return string[]
+function M.complete_bundle(_, arglead)
+  local names = vim.tbl_filter(
+    function(name) return vim.startswith(name, arglead) end,
+    vim.tbl_keys(require('nekifoch').config.bundles)
+  )
+  table.sort(names)
+  return names
+end
+
+---@param typed string
+---@param arglead string
+---@return string[]
+function M.complete_host(typed, arglead)
+  if typed ~= arglead then return {} end
+  return vim.tbl_filter(
+    function(action) return vim.startswith(action, arglead) end,
+    { 'save', 'forget' }
+  )
+end
+
+---@param _ string
+---@param arglead string
+---@return string[]
+function M.complete_file(_, arglead)
+  return vim.fn.getcompletion(arglead, 'file')
+end
+
+---Families the installer offers, the words already typed left out
+---@param typed string
+---@param arglead string
+---@return string[]
+function M.complete_installable(typed, arglead)
+  local done = #typed - #arglead
+  local matches = {}
+  for _, font in ipairs(require('nekifoch.installer').fonts) do
+    if vim.startswith(font.family:lower(), typed:lower()) then
+      table.insert(matches, font.family:sub(done + 1))
     end
+  end
+  return matches
+end
+
+---@param _ string
+---@param arglead string
+---@return string[]
+function M.complete_size(_, arglead)
+  return vim.startswith('default', arglead) and { 'default' } or {}
+end
+
+---Setting names, then `default` for the value
+---@param typed string
+---@param arglead string
+---@return string[]
+function M.complete_modify(typed, arglead)
+  local options = typed == arglead
+      and require('nekifoch.utils').font_modifications
+    or { 'default' }
+  return vim.tbl_filter(
+    function(option) return vim.startswith(option, arglead) end,
+    options
+  )
+end
+
+---Option names, then their known values with the current one first
+---@param typed string
+---@param arglead string
+---@return string[]
+function M.complete_option(typed, arglead)
+  local option = typed:match('^(%S+)%s')
+  local candidates
+  if not option then
+    candidates = vim.tbl_keys(func.settable_options)
+    table.sort(candidates)
   else
-    local formattedFonts, _ =
-      util.compareFontsWithKittyListFonts(util.getCachedInstalledFonts())
-    if formattedFonts then
-      local keys = vim.tbl_keys(formattedFonts)
-      table.sort(keys)
-      return keys
-    else
-      return {}
+    candidates = {}
+    local ok, current = pcall(func.set, { option })
+    if ok and current and current.value then
+      table.insert(candidates, current.value)
     end
+    for _, value in ipairs(func.settable_options[option] or {}) do
+      if value ~= candidates[1] then table.insert(candidates, value) end
+    end
+    table.insert(candidates, 'default')
+  end
+  return vim.tbl_filter(
+    function(candidate) return vim.startswith(candidate, arglead) end,
+    candidates
+  )
+end
+
+---@param typed string
+---@param arglead string
+---@return string[]
+function M.complete_list(typed, arglead)
+  if typed ~= arglead then return {} end
+  return vim.startswith('nerd', arglead) and { 'nerd' } or {}
+end
+
+M.completers = {
+  set_size = M.complete_size,
+  list = M.complete_list,
+  modify = M.complete_modify,
+  set = M.complete_option,
+  which = M.complete_font_option,
+  profile = M.complete_profile,
+  bundle = M.complete_bundle,
+  host = M.complete_host,
+  export = M.complete_file,
+  import = M.complete_file,
+  install = M.complete_installable,
+  set_font = M.complete_font,
+  apply = M.complete_font,
+  set_bold_font = M.complete_style_font,
+  set_italic_font = M.complete_style_font,
+  set_bold_italic_font = M.complete_style_font,
+  ladder = M.complete_font,
+  font_info = M.complete_font,
+}
+
+---@param arglead string
+---@param cmdline string
+---@param cursorpos integer
+---@return string[]
+function M.complete(arglead, cmdline, cursorpos)
+  local rest = cmdline:sub(1, cursorpos):match('^%S*%s+(.*)$')
+  if not rest then return {} end
+
+  local sub, typed = rest:match('^(%S+)%s+(.*)$')
+  if not sub then
+    return vim.tbl_filter(
+      function(name) return vim.startswith(name, arglead) end,
+      M.subcommands
+    )
   end
+
+  local completer = M.completers[sub]
+  if not completer then return {} end
+  return completer(typed, arglead)
 end
 
 return M
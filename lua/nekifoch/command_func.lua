@@ -1,4 +1,6 @@
 local util = require('nekifoch.utils')
+local cache = require('nekifoch.cache')
+local backend = require('nekifoch.backend')
 
 local M = {}
 
@@ -14,62 +16,895 @@ local M = {}
 --   end
 -- end
 
-M.set_font = function(opts)
-  if #opts.fargs < 2 then
-    print('Specify a font name')
-    return
-  end
+---@class NekifochFont
+---@field font string|nil Font family
+---@field size string|nil Font size, `default` when the override was removed
+---@field bold string|nil
+---@field italic string|nil
+---@field bold_italic string|nil
+---@field spec table<string, string>|nil Extended kitty fields like `style`
+---@field modify table<string, string>|nil `modify_font` value by setting
+---@field options table<string, string>|nil Other options set with `set`
+---@field action NekifochEditAction|nil How the config file was changed
+---@field scale number|nil Neovide scale factor set by size_up / size_down
+---@field theme string|nil Color theme switched along with the font
+
+-- Commands return their result, or `nil` and an error message
+
+---Resolve a font name or completion key to the full family name
+---@param args string[] Words of the font name, optionally quoted
+---@return string|nil family
+---@return string|nil err
+local function resolve_font(args)
+  if #args == 0 then return nil, 'Specify a font name' end
 
-  local formattedFonts, _ =
-    util.compareFontsWithKittyListFonts(util.getCachedInstalledFonts())
+  local formattedFonts, compatibleFonts = cache.get()
   if not formattedFonts then
-    print('Error: Fonts data is unavailable.')
-    return
+    return nil, 'Error: Fonts data is unavailable.'
+  end
+
+  local name = table.concat(args, ' ')
+  name = name:match('^"(.*)"$') or name:match("^'(.*)'$") or name
+
+  if formattedFonts[name] then return formattedFonts[name] end
+  for _, font in ipairs(compatibleFonts) do
+    if font:lower() == name:lower() then return font end
   end
+  -- ключ, полученный из автодополнения
+  local fontKey = name:gsub('%s+', '')
+  if formattedFonts[fontKey] then return formattedFonts[fontKey] end
+
+  return nil, 'Font not found: ' .. name
+end
 
-  local fontKey = opts.fargs[2] -- ключ, полученный из автодополнения
-  local fullFontName = formattedFonts[fontKey]
+---Edit everything of `change` but the theme in one batch
+---@param terminal NekifochBackend
+---@param change NekifochFont
+---@param track fun(action: NekifochEditAction)
+---@return table<string, string|false> previous Files before the change
+local function edit_fonts(terminal, change, track)
+  -- Edits stay in memory until all succeeded, each file is written once
+  return util.batch(function()
+    if change.font then
+      local spec = require('nekifoch.features').spec(change.font, change.spec)
+      track(terminal.set_font(change.font, nil, spec))
+    end
+    for _, style in ipairs(util.styles) do
+      if change[style] then
+        track(terminal.set_font(change[style], style, change.spec))
+      end
+    end
+    if change.size == 'default' then
+      if not terminal.unset_size then
+        error(terminal.name .. ' has no default font size', 0)
+      end
+      terminal.unset_size()
+    elseif change.size then
+      track(terminal.set_size(tonumber(change.size)))
+    end
+
+    for option, value in pairs(change.options or {}) do
+      if not terminal.set_option then
+        error(terminal.name .. ' has no option ' .. option, 0)
+      end
+      track(terminal.set_option(option, value))
+    end
+    for what, value in pairs(change.modify or {}) do
+      if not terminal.modify_font then
+        error(terminal.name .. ' has no modify_font', 0)
+      end
+      track(terminal.modify_font(what, value))
+    end
+  end)
+end
 
-  if not fullFontName then
-    print('Font not found: ' .. fontKey)
-    return
+---Write `change` through the terminal backend without reloading, setting
+---`change.action` when an option had to be added
+---@param change NekifochFont
+---@return table<string, string|false> previous Files before the change
+M.edit = function(change)
+  local terminal = backend.current()
+  -- Report the edit that had to add a missing option, if any
+  local function track(action)
+    if action ~= 'replaced' then change.action = action end
   end
 
-  util.replace_font_family(fullFontName)
+  if change.theme and not terminal.set_theme then
+    error(terminal.name .. ' has no themes', 0)
+  end
+  -- Backups of the edits and of the files the themes kitten changes share a
+  -- stamp
+  return require('nekifoch.backup').group(function()
+    local previous = edit_fonts(terminal, change, track)
+    if not change.theme then return previous end
+    -- The themes kitten writes the config itself, once the edits are on disk
+    local ok, themed = pcall(terminal.set_theme, change.theme)
+    if not ok then
+      util.restore_files(previous)
+      error(themed, 0)
+    end
+    for path, content in pairs(themed or {}) do
+      if previous[path] == nil then previous[path] = content end
+    end
+    return previous
+  end)
+end
 
-  if vim.fn.systemlist('pidof kitty')[1] ~= '' then
-    vim.cmd('silent !kill -USR1 $(pidof kitty)')
+---Apply the family and size of `change` to another target, the rest of it
+---only makes sense to the first one
+---@param target NekifochBackend
+---@param change NekifochFont
+M.sync = function(target, change)
+  if change.font then target.set_font(change.font) end
+  if change.size == 'default' then
+    if target.unset_size then target.unset_size() end
+  elseif change.size then
+    target.set_size(tonumber(change.size))
   end
+  target.reload(change)
 end
 
-M.set_size = function(opts)
-  util.replace_font_size(opts.fargs[2])
-  if vim.fn.systemlist('pidof kitty')[1] ~= '' then
-    vim.cmd('silent !kill -USR1 $(pidof kitty)')
+---Write `change` through the terminal backend and reload once, running the
+---`on_pre_change` and `on_post_change` hooks around it. With `confirm` set,
+---nothing is returned and the change is written once its diff is accepted
+---@param change NekifochFont
+---@param confirmed boolean|nil The diff was already accepted
+---@return NekifochFont|nil change
+---@return string|nil err
+M.apply = function(change, confirmed)
+  local config = require('nekifoch').config
+  if config.confirm and not confirmed then
+    require('nekifoch.diff').confirm(change, function()
+      local ok, result, err = pcall(M.apply, change, true)
+      if not ok or err then
+        return require('nekifoch.notify').error(ok and err or result)
+      end
+      require('nekifoch.command').formatters.apply(result)
+    end)
+    return nil
+  end
+
+  if config.on_pre_change and config.on_pre_change(change) == false then
+    return nil, 'Change cancelled by on_pre_change'
+  end
+
+  local previous = M.edit(change)
+  local terminal = backend.current()
+  local ok, err = terminal.reload(change)
+  if ok == false then
+    -- Keep the config kitty last accepted
+    util.restore_files(previous)
+    terminal.reload()
+    return nil,
+      terminal.name
+        .. ' rejected the change, the config was restored: '
+        .. (err or '')
+  end
+  for _, other in ipairs(backend.others()) do
+    M.sync(other, change)
   end
+  if change.font then require('nekifoch.recent').add(change.font) end
+  if config.on_post_change then config.on_post_change(change) end
+  return change
 end
 
--- TODO: add notify if notify in config
-M.list = function()
-  local _, availableFonts =
-    util.compareFontsWithKittyListFonts(util.getCachedInstalledFonts())
-  print('Available fonts:')
-  for _, font in ipairs(availableFonts) do
-    print(' - ' .. font)
+---Split trailing `style=Medium features=+ss01` fields off the font name
+---@param args string[]
+---@return string[] args
+---@return table<string, string>|nil spec
+local function split_spec(args)
+  local name, fields = table.concat(args, ' '):match('^(.-)%s+(%a[%w_]*=.*)$')
+  if not name then return args, nil end
+  local spec = {}
+  local parsed = require('nekifoch.kitty_conf').parse_font(fields)
+  for _, field in ipairs(parsed.fields) do
+    spec[field[1]] = field[2]
   end
+  return vim.split(name, ' ', { trimempty = true }), spec
 end
 
-M.check = function()
-  local current_font = util.get()
-  if current_font then
-    vim.notify(
-      current_font['font'] .. '\n\t' .. 'Font size: ' .. current_font['size'],
-      2,
-      { title = 'Current font' }
-    )
+---@param args string[] Words of the font name, then extended kitty fields
+---@param style NekifochStyle|nil Set the bold/italic font instead
+---@return NekifochFont|nil change
+---@return string|nil err
+M.set_font = function(args, style)
+  local fullFontName, err
+  local spec
+  args, spec = split_spec(args)
+  if style and table.concat(args, ' ') == 'auto' then
+    fullFontName = 'auto'
   else
-    vim.notify('Font family not found in configuration', 4, { title = 'Font' })
+    fullFontName, err = resolve_font(args)
+  end
+  if not fullFontName then return nil, err end
+
+  return M.apply({ [style or 'font'] = fullFontName, spec = spec })
+end
+
+---Set the family and size together, written in one pass with one reload.
+---The last argument is the size when it is a number or `default`
+---@param args string[] Words of the font name, then the size
+---@return NekifochFont|nil change
+---@return string|nil err
+M.apply_font = function(args)
+  local change = {}
+  local last = args[#args]
+  if last and (tonumber(last) or last == 'default') then
+    change.size = last
+    args = vim.list_slice(args, 1, #args - 1)
+  end
+  if #args > 0 then
+    local err
+    change.font, err = resolve_font(args)
+    if not change.font then return nil, err end
+  end
+  if not (change.font or change.size) then
+    return nil, 'Specify a font name and size'
+  end
+
+  if tonumber(change.size) then
+    local size, config = tonumber(change.size), require('nekifoch').config
+    if size < config.size_min or size > config.size_max then
+      return nil,
+        'Font size must be between '
+          .. config.size_min
+          .. ' and '
+          .. config.size_max
+    end
+  end
+  return M.apply(change)
+end
+
+---Round `size` to one decimal and keep it within `size_min`..`size_max`
+---@param size number
+---@return number
+M.clamp_size = function(size)
+  local config = require('nekifoch').config
+  size = math.floor(size * 10 + 0.5) / 10
+  return math.min(math.max(size, config.size_min), config.size_max)
+end
+
+---Set an absolute size, step the current one with `+2` / `-1.5`, or drop
+---the override with `default`
+---@param args string[]
+---@return NekifochFont|nil change
+---@return string|nil err
+M.set_size = function(args)
+  local value = args[1]
+  if not value then return nil, 'Specify a font size' end
+  if value == 'default' then return M.apply({ size = 'default' }) end
+
+  local size = tonumber(value)
+  if size and value:match('^[+-]') then
+    local current = tonumber(backend.current().get().size)
+    if not current then return nil, 'Font size not found in configuration' end
+    size = M.clamp_size(current + size)
+  end
+  if not size or size <= 0 then return nil, 'Invalid font size: ' .. value end
+
+  local config = require('nekifoch').config
+  if size < config.size_min or size > config.size_max then
+    return nil,
+      'Font size must be between '
+        .. config.size_min
+        .. ' and '
+        .. config.size_max
+  end
+
+  return M.apply({ size = tostring(M.clamp_size(size)) })
+end
+
+---Step the size `args[1]` times by `size_step` in a single write
+---@param args string[]
+---@param sign "'+'"|"'-'"
+---@return NekifochFont|nil change
+---@return string|nil err
+local function step_size(args, sign)
+  local count = tonumber(args[1] or 1)
+  if not count or count < 1 then return nil, 'Invalid count: ' .. args[1] end
+  local config = require('nekifoch').config
+  local step = math.floor(count) * config.size_step
+
+  if vim.g.neovide and config.neovide.scale ~= 'off' then
+    local delta = math.floor(count) * config.neovide.step
+    local factor = (vim.g.neovide_scale_factor or 1)
+      + (sign == '+' and delta or -delta)
+    -- Round away float noise, the factor can't reach zero
+    factor = math.max(config.neovide.step, math.floor(factor * 100 + 0.5) / 100)
+    vim.g.neovide_scale_factor = factor
+    if config.neovide.scale == 'only' then return { scale = factor } end
+  end
+
+  return M.set_size({ sign .. step })
+end
+
+---Grow the font by `size_step`, `[count]` times
+---@param args string[]
+---@return NekifochFont|nil change
+---@return string|nil err
+M.size_up = function(args) return step_size(args, '+') end
+
+---Shrink the font by `size_step`, `[count]` times
+---@param args string[]
+---@return NekifochFont|nil change
+---@return string|nil err
+M.size_down = function(args) return step_size(args, '-') end
+
+---@param args string[] `nerd` lists only Nerd Fonts
+---@return string[]|nil fonts
+---@return string|nil err
+M.list = function(args)
+  local _, availableFonts = cache.get()
+  if not args[1] then return availableFonts end
+  if args[1] ~= 'nerd' then return nil, 'Unknown filter: ' .. args[1] end
+  return require('nekifoch.nerd').filter(availableFonts or {})
+end
+
+---Preview a family at several sizes, defaults to the current font
+---@param args string[] Words of the font name
+---@return { font: string, sizes: number[] }|nil preview
+---@return string|nil err
+M.ladder = function(args)
+  local family, err
+  if #args == 0 then
+    family = backend.current().get().font
+    if not family then err = 'Font family not found in configuration' end
+  else
+    family, err = resolve_font(args)
+  end
+  if not family then return nil, err end
+
+  local sizes = require('nekifoch').config.preview.sizes
+  local ok, launch_err = require('nekifoch.preview').ladder(family, sizes)
+  if not ok then return nil, launch_err end
+
+  return { font = family, sizes = sizes }
+end
+
+---@param args string[] Words of the font name
+---@return NekifochFontInfo|nil info
+---@return string|nil err
+M.font_info = function(args)
+  if #args == 0 then return nil, 'Specify a font name' end
+
+  local _, compatibleFonts = cache.get()
+  local name = table.concat(args, ' ')
+  -- Fonts kitty can't use are described too, as not kitty compatible
+  local family = resolve_font(args)
+    or name:match('^"(.*)"$')
+    or name:match("^'(.*)'$")
+    or name
+
+  local info = util.font_info(family, compatibleFonts)
+  if not info then return nil, 'Font not found: ' .. name end
+  return info
+end
+
+---@class NekifochDetection
+---@field terminal string|nil Detected terminal
+---@field backend string Backend in use
+---@field config_path string
+---@field exists boolean
+---@field env table<string, string>
+---@field kitty_version integer[]|nil
+---@field diagnostics NekifochDiagnostic[] Problems in kitty.conf
+
+---@return NekifochDetection
+M.detect = function()
+  local detect = require('nekifoch.detect')
+  local terminal = backend.current()
+  local env = {}
+  for _, name in ipairs(detect.env_vars) do
+    env[name] = vim.env[name]
+  end
+
+  return {
+    terminal = detect.terminal(),
+    backend = terminal.name,
+    config_path = terminal.config_path(),
+    exists = vim.fn.filereadable(terminal.config_path()) == 1,
+    env = env,
+    kitty_version = util.kitty_version(),
+    diagnostics = terminal.name == 'kitty'
+        and vim.fn.filereadable(terminal.config_path()) == 1
+        and require('nekifoch.kitty_conf').diagnostics(terminal.config_path())
+      or {},
+  }
+end
+
+-- Options `which` reports by default
+M.font_options = {
+  'font_family',
+  'bold_font',
+  'italic_font',
+  'bold_italic_font',
+  'font_size',
+}
+
+---Where font options take effect, following kitty `include` directives
+---@param args string[] Options to look up, font options by default
+---@return NekifochDirective[]|nil directives
+---@return string|nil err
+M.which = function(args)
+  local terminal = backend.current()
+  if terminal.name ~= 'kitty' then
+    return nil, 'which is only supported for kitty'
+  end
+
+  local settings =
+    require('nekifoch.kitty_conf').effective(terminal.config_path())
+  local directives = {}
+  for _, option in ipairs(#args > 0 and args or M.font_options) do
+    if settings[option] then table.insert(directives, settings[option]) end
+  end
+  if #directives == 0 then return nil, 'Options are not set' end
+  return directives
+end
+
+-- Font related kitty options `set` accepts, with the values to complete
+M.settable_options = {
+  adjust_baseline = {},
+  adjust_column_width = {},
+  adjust_line_height = {},
+  box_drawing_scale = {},
+  disable_ligatures = { 'never', 'cursor', 'always' },
+  force_ltr = { 'yes', 'no' },
+  text_composition_strategy = { 'platform', 'legacy' },
+  text_fg_override_threshold = {},
+  undercurl_style = {
+    'thin-sparse',
+    'thin-dense',
+    'thick-sparse',
+    'thick-dense',
+  },
+  underline_exclusion = {},
+}
+
+---@class NekifochOptionValue
+---@field option string
+---@field value string|nil `nil` when kitty's default applies
+
+---Set one of `settable_options`, or show its current value without one.
+---`default` removes it from the config
+---@param args string[]
+---@return NekifochFont|NekifochOptionValue|nil
+---@return string|nil err
+M.set = function(args)
+  local option = args[1]
+  if not option then return nil, 'Specify an option' end
+  if not M.settable_options[option] then
+    return nil, 'Option not supported: ' .. option
+  end
+
+  local terminal = backend.current()
+  if terminal.name ~= 'kitty' then
+    return nil, 'set is only supported for kitty'
+  end
+  if #args == 1 then
+    local settings =
+      require('nekifoch.kitty_conf').effective(terminal.config_path())
+    return {
+      option = option,
+      value = settings[option] and settings[option].value,
+    }
+  end
+  local value = table.concat(vim.list_slice(args, 2), ' ')
+  return M.apply({ options = { [option] = value } })
+end
+
+---@class NekifochProfile
+---@field family string|nil
+---@field size number|nil
+---@field bold string|nil
+---@field italic string|nil
+---@field bold_italic string|nil
+
+---Apply a profile from `setup({ profiles = ... })` with a single reload
+---@param profile NekifochProfile
+---@param confirmed boolean|nil Skip `confirm`, for changes nobody asked for
+---@return NekifochFont|nil change
+---@return string|nil err
+M.apply_profile = function(profile, confirmed)
+  return M.apply({
+    font = profile.family,
+    size = profile.size and tostring(profile.size),
+    bold = profile.bold,
+    italic = profile.italic,
+    bold_italic = profile.bold_italic,
+  }, confirmed)
+end
+
+---Apply the parts of `profile` that differ from the config, nothing when
+---it already matches
+---@param profile NekifochProfile
+---@param confirmed boolean|nil Skip `confirm`, for changes nobody asked for
+---@return NekifochFont|nil change
+---@return string|nil err
+M.update_profile = function(profile, confirmed)
+  local ok, current = pcall(backend.current().get)
+  if not ok then return nil, current end
+
+  local changed = {}
+  local fields = { family = 'font', size = 'size' }
+  for _, style in ipairs(util.styles) do
+    fields[style] = style
+  end
+  for field, key in pairs(fields) do
+    local value, have = profile[field], current[key]
+    -- `15` and `15.0` are the same size
+    local same = field == 'size' and tonumber(value) == tonumber(have)
+      or tostring(value) == tostring(have)
+    if value ~= nil and not same then changed[field] = value end
+  end
+  if vim.tbl_isempty(changed) then return nil end
+  return M.apply_profile(changed, confirmed)
+end
+
+---@class NekifochBundle: NekifochProfile
+---@field theme string|nil Name of a theme of `kitty +kitten themes`
+
+---Switch to a theme and font from `bundles` with one reload, or pick one
+---when no name is given
+---@param args string[]
+---@return NekifochFont|nil change
+---@return string|nil err
+M.bundle = function(args)
+  local bundles = require('nekifoch').config.bundles
+  if #args == 0 then
+    if vim.tbl_isempty(bundles) then return nil, 'No bundles configured' end
+    local names = vim.tbl_keys(bundles)
+    table.sort(names)
+    local command = require('nekifoch.command')
+    local items = vim.tbl_map(function(name)
+      return {
+        label = name,
+        action = function() command.run('bundle', { name }) end,
+      }
+    end, names)
+    return require('nekifoch.picker').choose(items, 'Theme and font')
+  end
+
+  local name = table.concat(args, ' ')
+  local bundle = bundles[name]
+  if not bundle then return nil, 'Bundle not found: ' .. name end
+  return M.apply({
+    theme = bundle.theme,
+    font = bundle.family,
+    size = bundle.size and tostring(bundle.size),
+    bold = bundle.bold,
+    italic = bundle.italic,
+    bold_italic = bundle.bold_italic,
+  })
+end
+
+---Switch to a named profile, or pick one when no name is given
+---@param args string[]
+---@return NekifochFont|nil change
+---@return string|nil err
+M.profile = function(args)
+  local profiles = require('nekifoch').config.profiles
+  if #args == 0 then
+    if vim.tbl_isempty(profiles) then return nil, 'No profiles configured' end
+    require('nekifoch.picker').profile()
+    return nil
+  end
+
+  local name = table.concat(args, ' ')
+  if not profiles[name] then return nil, 'Profile not found: ' .. name end
+  return M.apply_profile(profiles[name])
+end
+
+---@class NekifochHost
+---@field name string Hostname
+---@field profile NekifochProfile|nil
+---@field action "'saved'"|"'forgotten'"|nil
+
+---Show this machine's profile, `save` the current font as its profile or
+---`forget` the saved one
+---@param args string[]
+---@return NekifochHost|nil
+---@return string|nil err
+M.host = function(args)
+  local hosts = require('nekifoch.hosts')
+  local action = args[1]
+  if action == 'save' then
+    local current = backend.current().get()
+    hosts.save({
+      family = current.font,
+      size = tonumber(current.size),
+      bold = current.bold,
+      italic = current.italic,
+      bold_italic = current.bold_italic,
+    })
+  elseif action == 'forget' then
+    if not hosts.forget() then
+      return nil, 'Nothing saved for ' .. hosts.name()
+    end
+  elseif action then
+    return nil, 'Unknown action: ' .. action
+  end
+  return {
+    name = hosts.name(),
+    profile = hosts.profile(),
+    action = action and (action == 'save' and 'saved' or 'forgotten'),
+  }
+end
+
+---@param args string[]
+---@return string|nil path
+local function settings_path(args)
+  local path = table.concat(args, ' ')
+  if path == '' then return nil end
+  return vim.fn.fnamemodify(vim.fn.expand(path), ':p')
+end
+
+---Write the font, features, favorites and profiles to a JSON file
+---@param args string[]
+---@return string|nil path
+---@return string|nil err
+M.export = function(args)
+  local path = settings_path(args)
+  if not path then return nil, 'Export needs a file' end
+  local err = require('nekifoch.settings').export(path)
+  if err then return nil, err end
+  return path
+end
+
+---Merge the settings of a file written by `export` and apply its font
+---@param args string[]
+---@return { path: string, change: NekifochFont|nil }|nil
+---@return string|nil err
+M.import = function(args)
+  local path = settings_path(args)
+  if not path then return nil, 'Import needs a file' end
+  local change, err = require('nekifoch.settings').import(path)
+  if err then return nil, err end
+  return { path = path, change = change }
+end
+
+---kitty.conf lines for the current font, put in the `+` register for
+---configs generated from templates
+---@return string[]|nil lines
+---@return string|nil err
+M.yank = function()
+  local kitty_conf = require('nekifoch.kitty_conf')
+  local current = backend.current().get()
+  if not current.font then return nil, 'No font family set' end
+
+  ---@param family string
+  ---@param spec table<string, string>|nil
+  ---@return string
+  local function value(family, spec)
+    local font = kitty_conf.parse_font(family)
+    local keys = vim.tbl_keys(spec or {})
+    table.sort(keys)
+    for _, key in ipairs(keys) do
+      kitty_conf.set_font_field(font, key, spec[key])
+    end
+    return kitty_conf.format_font(font)
+  end
+
+  local lines = { 'font_family ' .. value(current.font, current.spec) }
+  if current.size then table.insert(lines, 'font_size ' .. current.size) end
+  for _, style in ipairs(util.styles) do
+    if current[style] then
+      table.insert(lines, style .. '_font ' .. value(current[style]))
+    end
+  end
+  vim.fn.setreg('+', lines, 'l')
+  return lines
+end
+
+---DPI of the display and the size `dpi.sizes` maps it to
+---@return { dpi: number, size: number|nil }|nil
+---@return string|nil err
+M.dpi = function()
+  local dpi = require('nekifoch.dpi')
+  local value = dpi.query()
+  if not value then
+    return nil, 'Display DPI not found, xrandr or wlr-randr is needed'
+  end
+  return { dpi = value, size = dpi.size(value) }
+end
+
+---Download and install a font from the installer's list, or pick one
+---@param args string[]
+---@return nil
+---@return string|nil err
+M.install = function(args)
+  local installer = require('nekifoch.installer')
+  local family = table.concat(args, ' ')
+  if family == '' then return installer.pick() end
+  local font = installer.find(family)
+  if not font then return nil, 'Not in the list of fonts: ' .. family end
+  installer.install(font)
+end
+
+---Run `fc-cache -f` in the background, then rebuild the font list, for
+---font files copied into a font directory by hand
+---@return nil
+M.fc_cache = function()
+  util.require_executable('fc-cache', 'fontconfig')
+  require('nekifoch.notify').info('Updating the fontconfig cache')
+  require('nekifoch.installer').fc_cache(function(ok)
+    if not ok then
+      return require('nekifoch.notify').error('fc-cache failed, see the log')
+    end
+    cache.get_async(function(_, fonts)
+      require('nekifoch.notify').info(#fonts .. ' fonts found')
+    end)
+  end)
+end
+
+---Rebuild the font cache, e.g. after installing fonts
+---@return string[] fonts
+M.refresh_cache = function()
+  local _, fonts = cache.refresh()
+  return fonts
+end
+
+---Fonts next_font / prev_font move through, alphabetically
+---@return string[]
+local function cycle_fonts()
+  local source = require('nekifoch').config.cycle
+  local fonts
+  if source == 'favorites' then
+    fonts = require('nekifoch.favorites').list()
+  elseif source == 'recent' then
+    fonts = require('nekifoch.recent').fonts()
+  else
+    local _, list = cache.get()
+    fonts = list or {}
+  end
+  fonts = vim.list_extend({}, fonts)
+  table.sort(fonts, function(a, b) return a:lower() < b:lower() end)
+  return fonts
+end
+
+---Apply the font after or before the current one, wrapping around
+---@param step integer 1 or -1
+---@return NekifochFont|nil change
+---@return string|nil err
+local function cycle_font(step)
+  local fonts = cycle_fonts()
+  if #fonts == 0 then return nil, 'No fonts to cycle through' end
+
+  local current = backend.current().get().font
+  local index = step > 0 and 0 or #fonts + 1
+  for i, font in ipairs(fonts) do
+    if font == current then index = i end
+  end
+  return M.apply({ font = fonts[(index + step - 1) % #fonts + 1] })
+end
+
+---@return NekifochFont|nil change
+---@return string|nil err
+M.next_font = function() return cycle_font(1) end
+
+---@return NekifochFont|nil change
+---@return string|nil err
+M.prev_font = function() return cycle_font(-1) end
+
+---Pick from favorite fonts
+---@return nil
+---@return string|nil err
+M.favorites = function()
+  local fonts = require('nekifoch.favorites').list()
+  if #fonts == 0 then return nil, 'No favorite fonts' end
+  require('nekifoch.picker').font(nil, fonts)
+end
+
+---Pick from recently applied fonts
+---@return nil
+---@return string|nil err
+M.recent = function()
+  local fonts = require('nekifoch.recent').fonts()
+  if #fonts == 0 then return nil, 'No recently used fonts' end
+  require('nekifoch.picker').font(nil, fonts)
+end
+
+---Open the main menu
+---@return nil
+M.menu = function() require('nekifoch.picker').menu() end
+
+---Adjust cell metrics with kitty's `modify_font`: `cell_height 110%`,
+---`baseline 2px`, `underline_position 1`, or `default` to remove it. Without
+---a value, open a window to step it
+---@param args string[]
+---@return NekifochFont|nil change
+---@return string|nil err
+M.modify = function(args)
+  local what, value = args[1], args[2]
+  if not what then return require('nekifoch.picker').modify() end
+  if not vim.tbl_contains(util.font_modifications, what) then
+    return nil, 'Unknown modify_font setting: ' .. what
+  end
+  if not value then return require('nekifoch.picker').modify(what) end
+  local number = value:match('^([+-]?%d*%.?%d+)%%$')
+    or value:match('^([+-]?%d*%.?%d+)px$')
+    or value:match('^[+-]?%d*%.?%d+$')
+  if value ~= 'default' and not number then
+    return nil, 'Invalid modify_font value: ' .. value
+  end
+  return M.apply({ modify = { [what] = value } })
+end
+
+---Set OpenType features of the current family, e.g. `+ss01 -liga` or
+---`none`, or toggle them in a window without arguments
+---@param args string[]
+---@return NekifochFont|nil change
+---@return string|nil err
+M.features = function(args)
+  local terminal = backend.current()
+  if terminal.name ~= 'kitty' then return nil, 'Font features need kitty' end
+  if #args == 0 then return require('nekifoch.picker').features() end
+
+  local current = terminal.get()
+  if not current.font then
+    return nil, 'Font family not found in configuration'
+  end
+  local text = table.concat(args, ' ')
+  if text == 'none' then text = '' end
+  return M.apply({ font = current.font, spec = { features = text } })
+end
+
+---@class NekifochCoverage
+---@field font string
+---@field text string
+---@field missing string[] Characters that would show as boxes
+
+---Check that the current font, or `family`, has glyphs for all of `args`
+---@param args string[] Words of the text
+---@param family string|nil
+---@return NekifochCoverage|nil
+---@return string|nil err
+M.coverage = function(args, family)
+  if #args == 0 then return nil, 'Specify the text to check' end
+  family = family or backend.current().get().font
+  if not family then
+    return nil, 'Font family not found in configuration'
+  end
+
+  local text = table.concat(args, ' ')
+  local missing = util.missing_glyphs(family, text)
+  if not missing then return nil, 'Font not found: ' .. family end
+  return { font = family, text = text, missing = missing }
+end
+
+---Show subcommands, keymaps and options
+M.help = function() require('nekifoch.picker').help() end
+
+---Choose family, size and styles in a row, written once at the end
+M.wizard = function() require('nekifoch.wizard').start() end
+
+---Open the log in a split
+---@return nil
+---@return string|nil err
+M.log = function()
+  local file = require('nekifoch.log').file()
+  if vim.fn.filereadable(file) == 0 then return nil, 'Nothing logged yet' end
+  vim.cmd('split ' .. vim.fn.fnameescape(file))
+  vim.cmd('normal! G')
+end
+
+---Undo the last change made to the config files
+---@return NekifochBackup[]|nil restored
+---@return string|nil err
+M.restore = function()
+  local restored = require('nekifoch.backup').restore()
+  if #restored == 0 then return nil, 'No backup to restore' end
+  backend.current().reload()
+  return restored
+end
+
+---@return NekifochFont|nil current
+---@return string|nil err
+M.check = function()
+  local current_font = backend.current().get()
+  if not current_font.font then
+    return nil, 'Font family not found in configuration'
   end
+  return current_font
 end
 
 return M
@@ -0,0 +1,110 @@
+local M = {}
+
+-- Environment variables inspected to find out the running terminal
+M.env_vars = {
+  'KITTY_PID',
+  'KITTY_WINDOW_ID',
+  'KITTY_CONFIG_DIRECTORY',
+  'ALACRITTY_WINDOW_ID',
+  'ALACRITTY_SOCKET',
+  'WT_SESSION',
+  'TERMINAL_NAME',
+  'TERM',
+  'TERM_PROGRAM',
+}
+
+local home = vim.loop.os_homedir()
+
+---@return string
+local function config_home()
+  return vim.env.XDG_CONFIG_HOME or (home .. '/.config')
+end
+
+-- Candidate configuration files per terminal, first existing one wins
+local candidates = {
+  kitty = function()
+    local paths = {}
+    if vim.env.KITTY_CONFIG_DIRECTORY then
+      table.insert(paths, vim.env.KITTY_CONFIG_DIRECTORY .. '/kitty.conf')
+    end
+    table.insert(paths, config_home() .. '/kitty/kitty.conf')
+    if vim.fn.has('mac') == 1 then
+      table.insert(paths, home .. '/Library/Preferences/kitty/kitty.conf')
+    end
+    return paths
+  end,
+  alacritty = function()
+    local paths = {
+      config_home() .. '/alacritty/alacritty.toml',
+      home .. '/.config/alacritty/alacritty.toml',
+      home .. '/.alacritty.toml',
+    }
+    if vim.env.APPDATA then
+      table.insert(paths, 1, vim.env.APPDATA .. '/alacritty/alacritty.toml')
+    end
+    return paths
+  end,
+  rio = function()
+    local paths = { config_home() .. '/rio/config.toml' }
+    if vim.env.LOCALAPPDATA then
+      table.insert(paths, 1, vim.env.LOCALAPPDATA .. '/rio/config.toml')
+    end
+    return paths
+  end,
+  contour = function()
+    return {
+      config_home() .. '/contour/contour.yml',
+      home .. '/.config/contour/contour.yml',
+    }
+  end,
+  windows_terminal = function()
+    local packages = (vim.env.LOCALAPPDATA or (home .. '/AppData/Local'))
+      .. '/Packages/'
+    return {
+      packages
+        .. 'Microsoft.WindowsTerminal_8wekyb3d8bbwe/LocalState/settings.json',
+      packages
+        .. 'Microsoft.WindowsTerminalPreview_8wekyb3d8bbwe'
+        .. '/LocalState/settings.json',
+      (vim.env.LOCALAPPDATA or (home .. '/AppData/Local'))
+        .. '/Microsoft/Windows Terminal/settings.json',
+    }
+  end,
+}
+
+---Terminal Neovim is running in, `nil` when it can't be told
+---@return string|nil
+M.terminal = function()
+  local term = vim.env.TERM or ''
+  local program = (vim.env.TERM_PROGRAM or ''):lower()
+
+  if vim.env.KITTY_PID or vim.env.KITTY_WINDOW_ID or term == 'xterm-kitty' then
+    return 'kitty'
+  end
+  if
+    vim.env.ALACRITTY_WINDOW_ID
+    or vim.env.ALACRITTY_SOCKET
+    or term == 'alacritty'
+    or program == 'alacritty'
+  then
+    return 'alacritty'
+  end
+  if program == 'rio' or term == 'rio' then return 'rio' end
+  if vim.env.TERMINAL_NAME == 'contour' or term == 'contour' then
+    return 'contour'
+  end
+  if vim.env.WT_SESSION then return 'windows_terminal' end
+end
+
+---Configuration file of `terminal`, the conventional path if none exists
+---@param terminal string
+---@return string
+M.config_path = function(terminal)
+  local paths = candidates[terminal]()
+  for _, path in ipairs(paths) do
+    if vim.fn.filereadable(path) == 1 then return path end
+  end
+  return paths[1]
+end
+
+return M
@@ -0,0 +1,74 @@
+-- What a change would write to the terminal config, shown before writing it
+local util = require('nekifoch.utils')
+
+local M = {}
+
+---@class NekifochFileDiff
+---@field file string
+---@field diff string Unified diff without file headers
+
+---Edits `change` would make, one diff per file
+---@param change NekifochFont
+---@return NekifochFileDiff[]
+M.compute = function(change)
+  local files = util.dry_run(
+    function() require('nekifoch.command_func').edit(vim.deepcopy(change)) end
+  )
+  local diffs = {}
+  for file, content in pairs(files) do
+    local old = vim.fn.filereadable(file) == 1 and util.read_file(file) or ''
+    local diff = vim.diff(old, content, { ctxlen = 1 })
+    if diff ~= '' then table.insert(diffs, { file = file, diff = diff }) end
+  end
+  table.sort(diffs, function(a, b) return a.file < b.file end)
+  return diffs
+end
+
+---@param diffs NekifochFileDiff[]
+---@return string[]
+M.lines = function(diffs)
+  local lines = {}
+  for _, diff in ipairs(diffs) do
+    table.insert(lines, '+++ ' .. vim.fn.fnamemodify(diff.file, ':~'))
+    vim.list_extend(lines, vim.split(vim.trim(diff.diff), '\n'))
+  end
+  return lines
+end
+
+---Show what `change` would write and call `on_confirm` once accepted, or
+---`on_cancel`. Without anything to write it is accepted right away
+---@param change NekifochFont
+---@param on_confirm fun()
+---@param on_cancel fun()|nil
+M.confirm = function(change, on_confirm, on_cancel)
+  on_cancel = on_cancel or function() end
+  local lines = M.lines(M.compute(change))
+  if #lines == 0 then return on_confirm() end
+  if require('nekifoch').config.picker ~= 'ui_select' then
+    return require('nekifoch.nui_diff')(lines, on_confirm, on_cancel)
+  end
+
+  local chunks = {}
+  for _, line in ipairs(lines) do
+    local first = line:sub(1, 1)
+    local group = first == '+' and 'DiffAdd'
+      or first == '-' and 'DiffDelete'
+      or first == '@' and 'NekifochHint'
+      or nil
+    table.insert(chunks, { line .. '\n', group })
+  end
+  vim.api.nvim_echo(chunks, false, {})
+  vim.ui.select(
+    { 'Write', 'Cancel' },
+    { prompt = 'Write these changes?', kind = 'nekifoch' },
+    function(choice)
+      if choice == 'Write' then
+        on_confirm()
+      else
+        on_cancel()
+      end
+    end
+  )
+end
+
+return M
@@ -0,0 +1,178 @@
+-- Font size from the display's DPI, so text keeps its physical size when
+-- the terminal moves between monitors
+local util = require('nekifoch.utils')
+
+local M = {}
+
+---@class NekifochOutput
+---@field name string
+---@field x integer
+---@field y integer
+---@field width integer Pixels
+---@field height integer Pixels
+---@field dpi number
+---@field primary boolean
+
+---@param width integer Pixels
+---@param mm number
+---@return number
+local function dpi_of(width, mm) return width / (mm / 25.4) end
+
+---Connected outputs from `xrandr`, Wayland compositors report them through
+---XWayland too
+---@return NekifochOutput[]
+local function xrandr_outputs()
+  if vim.fn.executable('xrandr') == 0 then return {} end
+  local lines = util.systemlist({ 'xrandr', '--query' })
+  if vim.v.shell_error ~= 0 then return {} end
+
+  local outputs = {}
+  for _, line in ipairs(lines) do
+    -- eDP-1 connected primary 2560x1600+0+0 (normal ...) 286mm x 179mm
+    local name, width, height, x, y, mm = line:match(
+      '^(%S+) connected.- (%d+)x(%d+)%+(%d+)%+(%d+).- (%d+)mm x'
+    )
+    if name and tonumber(mm) > 0 then
+      table.insert(outputs, {
+        name = name,
+        x = tonumber(x),
+        y = tonumber(y),
+        width = tonumber(width),
+        height = tonumber(height),
+        dpi = dpi_of(tonumber(width), tonumber(mm)),
+        primary = line:find(' primary ', 1, true) ~= nil,
+      })
+    end
+  end
+  return outputs
+end
+
+---Enabled outputs from `wlr-randr`, for native Wayland without XWayland
+---@return NekifochOutput[]
+local function wayland_outputs()
+  if vim.fn.executable('wlr-randr') == 0 then return {} end
+  local output = util.system({ 'wlr-randr', '--json' })
+  if vim.v.shell_error ~= 0 then return {} end
+  local ok, heads = pcall(vim.json.decode, output)
+  if not ok or type(heads) ~= 'table' then return {} end
+
+  local outputs = {}
+  for _, head in ipairs(heads) do
+    local mm = head.physical_size and head.physical_size.width or 0
+    local mode = vim.tbl_filter(
+      function(m) return m.current end,
+      head.modes or {}
+    )[1]
+    if head.enabled and mode and mm > 0 then
+      table.insert(outputs, {
+        name = head.name,
+        x = head.position and head.position.x or 0,
+        y = head.position and head.position.y or 0,
+        width = mode.width,
+        height = mode.height,
+        dpi = dpi_of(mode.width, mm),
+        primary = false,
+      })
+    end
+  end
+  return outputs
+end
+
+---Center of kitty's window from `$WINDOWID`, which kitty only sets on X11
+---@return integer|nil x
+---@return integer|nil y
+local function window_center()
+  local id = vim.env.WINDOWID
+  if not id or vim.fn.executable('xdotool') == 0 then return nil end
+  local output = util.system({ 'xdotool', 'getwindowgeometry', '--shell', id })
+  if vim.v.shell_error ~= 0 then return nil end
+  local geometry = {}
+  for key, value in output:gmatch('(%u+)=(%d+)') do
+    geometry[key] = tonumber(value)
+  end
+  if not (geometry.X and geometry.WIDTH) then return nil end
+  return geometry.X + math.floor(geometry.WIDTH / 2),
+    geometry.Y + math.floor(geometry.HEIGHT / 2)
+end
+
+---Name of the focused output under sway or Hyprland, where kitty runs as a
+---native Wayland window that `xdotool` can't see
+---@return string|nil
+local function focused_output()
+  local cmd
+  if vim.fn.executable('swaymsg') == 1 then
+    cmd = { 'swaymsg', '-r', '-t', 'get_outputs' }
+  elseif vim.fn.executable('hyprctl') == 1 then
+    cmd = { 'hyprctl', '-j', 'monitors' }
+  else
+    return nil
+  end
+  local output = util.system(cmd)
+  if vim.v.shell_error ~= 0 then return nil end
+  local ok, outputs = pcall(vim.json.decode, output)
+  if not ok or type(outputs) ~= 'table' then return nil end
+  for _, o in ipairs(outputs) do
+    if o.focused then return o.name end
+  end
+end
+
+---DPI of the display kitty is on: the output holding the center of its
+---window, the focused output on Wayland, else the primary one or the first
+---@return number|nil
+M.query = function()
+  local outputs = xrandr_outputs()
+  if #outputs == 0 then outputs = wayland_outputs() end
+  if #outputs == 0 then return nil end
+
+  local x, y = window_center()
+  if x then
+    for _, o in ipairs(outputs) do
+      local inside_x = x >= o.x and x < o.x + o.width
+      if inside_x and y >= o.y and y < o.y + o.height then return o.dpi end
+    end
+  end
+  local focused = focused_output()
+  for _, o in ipairs(outputs) do
+    if o.name == focused then return o.dpi end
+  end
+  for _, o in ipairs(outputs) do
+    if o.primary then return o.dpi end
+  end
+  return outputs[1].dpi
+end
+
+---Size configured for `dpi`, the first range whose `max` it doesn't exceed
+---@param dpi number
+---@return number|nil
+M.size = function(dpi)
+  for _, range in ipairs(require('nekifoch').config.dpi.sizes) do
+    if not range.max or dpi <= range.max then return range.size end
+  end
+end
+
+---Set the size for the current display when it differs from the config
+M.apply = function()
+  local dpi = M.query()
+  local size = dpi and M.size(dpi)
+  if not size then return end
+  local ok, current = pcall(require('nekifoch.backend').current().get)
+  if ok and tonumber(current.size) == size then return end
+
+  require('nekifoch.log').info(
+    string.format('%.0f dpi, font size %s', dpi, size)
+  )
+  require('nekifoch.command').run('set_size', { tostring(size) })
+end
+
+---Check the display whenever the terminal regains focus, e.g. after
+---moving to another monitor
+M.start = function()
+  local group = vim.api.nvim_create_augroup('NekifochDpi', { clear = true })
+  vim.api.nvim_create_autocmd('FocusGained', {
+    group = group,
+    callback = function() M.apply() end,
+  })
+  vim.schedule(M.apply)
+end
+
+return M
@@ -0,0 +1,42 @@
+local state = require('nekifoch.state')
+
+local M = {}
+
+---@return string[]
+M.list = function() return state.get('favorites') or {} end
+
+---@param font string
+---@return boolean
+M.is = function(font) return vim.tbl_contains(M.list(), font) end
+
+---Add `font` to favorites or remove it
+---@param font string
+---@return boolean favorite Whether it is a favorite now
+M.toggle = function(font)
+  local favorites = vim.tbl_filter(
+    function(favorite) return favorite ~= font end,
+    M.list()
+  )
+  local added = #favorites == #M.list()
+  if added then table.insert(favorites, font) end
+  table.sort(favorites)
+  state.set('favorites', favorites)
+  return added
+end
+
+---`fonts` with favorites moved to the top, keeping the order otherwise
+---@param fonts string[]
+---@return string[]
+M.first = function(fonts)
+  local lookup = {}
+  for _, font in ipairs(M.list()) do
+    lookup[font] = true
+  end
+  local favorites, rest = {}, {}
+  for _, font in ipairs(fonts) do
+    table.insert(lookup[font] and favorites or rest, font)
+  end
+  return vim.list_extend(favorites, rest)
+end
+
+return M
@@ -0,0 +1,84 @@
+-- OpenType features written to kitty's extended `features=` field and
+-- remembered for every family
+local state = require('nekifoch.state')
+
+local M = {}
+
+-- Offered by `:Nekifoch features`, others can be typed as arguments
+M.known = {
+  'calt',
+  'liga',
+  'dlig',
+  'zero',
+  'onum',
+  'tnum',
+  'ss01',
+  'ss02',
+  'ss03',
+  'ss04',
+  'ss05',
+  'ss06',
+  'ss07',
+  'ss08',
+  'ss09',
+  'ss10',
+}
+
+---@class NekifochFeature
+---@field name string
+---@field enabled boolean Written as `+name`, `-name` when false
+
+---@param text string|nil Features as kitty takes them, e.g. `+ss01 -liga`
+---@return NekifochFeature[]
+M.parse = function(text)
+  local features = {}
+  for word in (text or ''):gmatch('%S+') do
+    local sign, name, value = word:match('^([+-]?)([^=]+)=?(.*)$')
+    table.insert(features, {
+      name = name,
+      enabled = sign ~= '-' and value ~= '0',
+    })
+  end
+  return features
+end
+
+---@param features NekifochFeature[]
+---@return string
+M.format = function(features)
+  local words = {}
+  for _, feature in ipairs(features) do
+    table.insert(words, (feature.enabled and '+' or '-') .. feature.name)
+  end
+  return table.concat(words, ' ')
+end
+
+---Features last written for `family`
+---@param family string
+---@return string|nil
+M.saved = function(family) return (state.get('features') or {})[family] end
+
+---@param family string
+---@param text string Empty to forget them
+M.save = function(family, text)
+  local saved = state.get('features') or {}
+  saved[family] = text ~= '' and text or nil
+  state.set('features', saved)
+end
+
+---Extended fields to write with `family`: features set in `spec` are
+---remembered, otherwise the ones saved for the family replace those of the
+---previous family. A dry run for the confirm diff remembers nothing
+---@param family string
+---@param spec table<string, string>|nil
+---@return table<string, string>
+M.spec = function(family, spec)
+  spec = vim.deepcopy(spec or {})
+  if spec.features then
+    if not require('nekifoch.utils').dry then M.save(family, spec.features) end
+  else
+    spec.features = M.saved(family) or ''
+  end
+  return spec
+end
+
+return M
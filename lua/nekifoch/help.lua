@@ -0,0 +1,111 @@
+-- Text of `:Nekifoch help`: subcommands, keymaps and the options in effect
+local M = {}
+
+-- Arguments and a short description of each subcommand
+---@type table<string, { [1]: string, [2]: string }>
+M.usage = {
+  check = { '', 'show the current font, size and styles' },
+  set_font = { '{family} [key=value...]', 'set the font family' },
+  set_bold_font = { '{family}|auto', 'set the bold font' },
+  set_italic_font = { '{family}|auto', 'set the italic font' },
+  set_bold_italic_font = { '{family}|auto', 'set the bold italic font' },
+  set_size = { '{size}|+{n}|-{n}|default', 'set or step the font size' },
+  apply = { '[family] [size]', 'set family and size in one write' },
+  size_up = { '[count]', 'grow the font by size_step' },
+  size_down = { '[count]', 'shrink the font by size_step' },
+  list = { '[nerd]', 'list fonts the terminal can use' },
+  ladder = { '[family]', 'preview a family at several sizes' },
+  font_info = { '{family}', 'styles, files and support of a font' },
+  coverage = { '{text}', 'check the font has glyphs for text' },
+  detect = { '', 'terminal, config file and kitty.conf problems' },
+  which = { '[option...]', 'where font options take effect' },
+  restore = { '', 'undo the last change to a config file' },
+  profile = { '[name]', 'switch to a named profile' },
+  bundle = { '[name]', 'switch kitty theme and font together' },
+  host = { '[save|forget]', "this machine's profile" },
+  dpi = { '', 'display DPI and the size it maps to' },
+  yank = { '', 'copy kitty.conf lines for the current font' },
+  export = { '{file}', 'write font, favorites and profiles to JSON' },
+  import = { '{file}', 'merge settings from export and apply' },
+  menu = { '', 'menu of all actions' },
+  wizard = { '', 'family, size and styles in a row' },
+  log = { '', 'open the log' },
+  favorites = { '', 'pick from favorite fonts' },
+  recent = { '', 'pick from recently applied fonts' },
+  next_font = { '', 'cycle to the next font' },
+  prev_font = { '', 'cycle to the previous font' },
+  features = { '[+feature -feature...|none]', 'OpenType features' },
+  modify = { '[setting] [value|default]', 'adjust modify_font settings' },
+  set = { '{option} [value|default]', 'set a font related option' },
+  install = { '[family]', 'download and install a coding font' },
+  fc_cache = { '', 'run fc-cache, then rebuild the font cache' },
+  refresh_cache = { '', 'rebuild the font cache' },
+  refresh = { '', 'same as refresh_cache' },
+  help = { '', 'this window' },
+}
+
+---@param value any
+---@return string
+local function inline(value)
+  if type(value) == 'function' then return '<function>' end
+  return (vim.inspect(value, { newline = ' ', indent = '' }))
+end
+
+---`key = value` lines of `options`, nested tables flattened with dots
+---@param options table
+---@param prefix string
+---@param lines string[]
+local function flatten(options, prefix, lines)
+  local keys = vim.tbl_keys(options)
+  table.sort(keys, function(a, b) return tostring(a) < tostring(b) end)
+  for _, key in ipairs(keys) do
+    local value = options[key]
+    local name = prefix .. tostring(key)
+    if type(value) == 'table' and not vim.tbl_islist(value) then
+      if vim.tbl_isempty(value) then
+        table.insert(lines, '  ' .. name .. ' = {}')
+      else
+        flatten(value, name .. '.', lines)
+      end
+    else
+      table.insert(lines, '  ' .. name .. ' = ' .. inline(value))
+    end
+  end
+end
+
+---@return string[]
+M.lines = function()
+  local config = require('nekifoch').config
+  local lines = { 'Subcommands', '' }
+
+  local names = require('nekifoch.command').subcommands
+  local width = 0
+  for _, name in ipairs(names) do
+    local args = (M.usage[name] or {})[1] or ''
+    width = math.max(width, #name + #args + 1)
+  end
+  for _, name in ipairs(names) do
+    local usage = M.usage[name] or { '', '' }
+    local call = vim.trim(name .. ' ' .. usage[1])
+    table.insert(
+      lines,
+      '  ' .. call .. string.rep(' ', width - #call + 2) .. usage[2]
+    )
+  end
+
+  vim.list_extend(lines, { '', 'Keymaps', '' })
+  local actions = vim.tbl_keys(config.keymaps)
+  table.sort(actions)
+  for _, action in ipairs(actions) do
+    local keys = require('nekifoch.ui').keys(action)
+    table.insert(lines, '  ' .. action .. ': ' .. table.concat(keys, ' '))
+  end
+
+  vim.list_extend(lines, { '', 'Options', '' })
+  local options = vim.deepcopy(config)
+  options.keymaps = nil
+  flatten(options, '', lines)
+  return lines
+end
+
+return M
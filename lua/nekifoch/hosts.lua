@@ -0,0 +1,46 @@
+-- Fonts per machine, for a Neovim config shared between a hi-dpi laptop
+-- and a desktop. Saved host profiles win over `profiles_by_host`
+local state = require('nekifoch.state')
+
+local M = {}
+
+---@return string
+M.name = function() return vim.loop.os_gethostname() end
+
+---Profile of this machine, `nil` without one
+---@return NekifochProfile|nil
+M.profile = function()
+  local configured = require('nekifoch').config.profiles_by_host[M.name()]
+  local saved = (state.get('hosts') or {})[M.name()]
+  if not configured and not saved then return nil end
+  return vim.tbl_extend('force', configured or {}, saved or {})
+end
+
+---Remember `profile` for this machine
+---@param profile NekifochProfile
+M.save = function(profile)
+  local hosts = state.get('hosts') or {}
+  hosts[M.name()] = profile
+  state.set('hosts', hosts)
+end
+
+---Forget the profile saved for this machine
+---@return boolean forgotten
+M.forget = function()
+  local hosts = state.get('hosts') or {}
+  if not hosts[M.name()] then return false end
+  hosts[M.name()] = nil
+  state.set('hosts', hosts)
+  return true
+end
+
+---Apply this machine's profile where it differs from the config, so
+---starting Neovim doesn't rewrite and reload an unchanged config
+M.apply = function()
+  local profile = M.profile()
+  if not profile then return end
+  local _, err = require('nekifoch.command_func').update_profile(profile)
+  if err then require('nekifoch.notify').error(err) end
+end
+
+return M
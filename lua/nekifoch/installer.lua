@@ -0,0 +1,201 @@
+-- Popular coding fonts downloaded from Nerd Fonts releases or the
+-- google/fonts repository into the user's font directory
+local util = require('nekifoch.utils')
+local notify = require('nekifoch.notify')
+
+local M = {}
+
+---@class NekifochInstallable
+---@field family string Family once installed
+---@field source "'nerd'"|"'google'"
+---@field asset string|nil Name of the Nerd Fonts release archive
+---@field path string|nil Directory of the family in google/fonts
+
+---@param asset string
+---@param family string
+---@return NekifochInstallable
+local function nerd(asset, family)
+  return { family = family, source = 'nerd', asset = asset }
+end
+
+---@param family string
+---@param path string
+---@return NekifochInstallable
+local function google(family, path)
+  return { family = family, source = 'google', path = path }
+end
+
+---@type NekifochInstallable[]
+M.fonts = {
+  nerd('JetBrainsMono', 'JetBrainsMono Nerd Font'),
+  nerd('FiraCode', 'FiraCode Nerd Font'),
+  nerd('Hack', 'Hack Nerd Font'),
+  nerd('IosevkaTerm', 'IosevkaTerm Nerd Font'),
+  nerd('CascadiaCode', 'CaskaydiaCove Nerd Font'),
+  nerd('Meslo', 'MesloLGS Nerd Font'),
+  nerd('SourceCodePro', 'SauceCodePro Nerd Font'),
+  nerd('VictorMono', 'VictorMono Nerd Font'),
+  nerd('UbuntuMono', 'UbuntuMono Nerd Font'),
+  nerd('Monaspace', 'MonaspiceNe Nerd Font'),
+  google('JetBrains Mono', 'ofl/jetbrainsmono'),
+  google('Fira Code', 'ofl/firacode'),
+  google('Source Code Pro', 'ofl/sourcecodepro'),
+  google('IBM Plex Mono', 'ofl/ibmplexmono'),
+  google('Roboto Mono', 'apache/robotomono'),
+  google('Inconsolata', 'ofl/inconsolata'),
+  google('Space Mono', 'ofl/spacemono'),
+  google('Ubuntu Mono', 'ufl/ubuntumono'),
+}
+
+---@param font NekifochInstallable
+---@return string
+local function url(font)
+  if font.source == 'nerd' then
+    return 'https://github.com/ryanoasis/nerd-fonts/releases/latest/download/'
+      .. font.asset
+      .. '.zip'
+  end
+  -- Lists the files of the family, fonts.google.com has no download any more
+  return 'https://api.github.com/repos/google/fonts/contents/' .. font.path
+end
+
+---@param font NekifochInstallable
+---@return string
+M.label = function(font)
+  return font.family
+    .. (font.source == 'nerd' and ' (Nerd Fonts)' or ' (Google Fonts)')
+end
+
+---@param family string
+---@return NekifochInstallable|nil
+M.find = function(family)
+  for _, font in ipairs(M.fonts) do
+    if font.family:lower() == family:lower() then return font end
+  end
+end
+
+---Directory fonts are installed to, one subdirectory per family
+---@return string
+M.dir = function()
+  local home = vim.loop.os_homedir()
+  if util.is_mac() then return home .. '/Library/Fonts' end
+  return (vim.env.XDG_DATA_HOME or home .. '/.local/share') .. '/fonts'
+end
+
+---Rebuild the fontconfig cache, then the font list
+---@param callback fun(ok: boolean)
+M.fc_cache = function(callback)
+  if vim.fn.executable('fc-cache') == 0 then
+    -- Without fontconfig new files are found by the system on their own
+    require('nekifoch.cache').invalidate()
+    return callback(true)
+  end
+  util.run_async({ 'fc-cache', '-f' }, function(ok)
+    require('nekifoch.cache').invalidate()
+    callback(ok)
+  end)
+end
+
+---Offer to apply `family` once the font list has it
+---@param family string
+local function offer(family)
+  require('nekifoch.cache').get_async(function(_, list)
+    if not vim.tbl_contains(list, family) then
+      return notify.warn(family .. ' was installed but is not listed yet')
+    end
+    vim.ui.select(
+      { 'Apply', 'Later' },
+      { prompt = family .. ' installed', kind = 'nekifoch' },
+      function(choice)
+        if choice == 'Apply' then
+          require('nekifoch.command').run('set_font', { family })
+        end
+      end
+    )
+  end)
+end
+
+---Download the font files of a google/fonts family straight into `dir`
+---@param font NekifochInstallable
+---@param dir string
+---@param callback fun()
+local function install_google(font, dir, callback)
+  util.read_async({ 'curl', '-fsSL', url(font) }, function(listing)
+    local ok, entries = pcall(vim.json.decode, listing)
+    local cmd = { 'curl', '-fsSL' }
+    for _, entry in ipairs(ok and type(entries) == 'table' and entries or {}) do
+      if type(entry) == 'table' and tostring(entry.name):match('%.ttf$') then
+        vim.list_extend(cmd, { '-o', dir .. '/' .. entry.name })
+        cmd[#cmd + 1] = entry.download_url
+      end
+    end
+    if #cmd == 2 then
+      return notify.error('Could not download ' .. font.family)
+    end
+    vim.fn.mkdir(dir, 'p')
+    util.run_async(cmd, function(downloaded)
+      if not downloaded then
+        return notify.error('Could not download ' .. font.family)
+      end
+      callback()
+    end)
+  end)
+end
+
+---Download `font`, unpack it into the font directory, rebuild the caches
+---and offer to apply it
+---@param font NekifochInstallable
+M.install = function(font)
+  if util.is_windows() then
+    error('Installing fonts is not supported on Windows', 0)
+  end
+  util.require_executable('curl', 'curl')
+
+  local dir = M.dir() .. '/' .. font.family:gsub(' ', '')
+  notify.info('Downloading ' .. M.label(font))
+  if font.source == 'google' then
+    return install_google(
+      font,
+      dir,
+      function() M.fc_cache(function() offer(font.family) end) end
+    )
+  end
+
+  util.require_executable('unzip', 'unzip')
+  local archive = vim.fn.tempname() .. '.zip'
+  util.run_async({ 'curl', '-fsSL', '-o', archive, url(font) }, function(ok)
+    if not ok then
+      os.remove(archive)
+      return notify.error('Could not download ' .. font.family)
+    end
+    vim.fn.mkdir(dir, 'p')
+    util.run_async(
+      { 'unzip', '-o', '-q', archive, '-d', dir },
+      function(unpacked)
+        os.remove(archive)
+        if not unpacked then
+          return notify.error('Could not unpack ' .. font.family)
+        end
+        M.fc_cache(function() offer(font.family) end)
+      end
+    )
+  end)
+end
+
+---Pick one of the fonts and install it
+M.pick = function()
+  local items = vim.tbl_map(
+    function(font)
+      return {
+        label = M.label(font),
+        action = function()
+          require('nekifoch.command').run('install', { font.family })
+        end,
+      }
+    end,
+    M.fonts
+  )
+  require('nekifoch.picker').choose(items, 'Install a font')
+end
+
+return M
@@ -0,0 +1,364 @@
+local util = require('nekifoch.utils')
+
+local M = {}
+
+---@class NekifochDirective
+---@field option string
+---@field value string
+---@field file string File or `$VAR` for `envinclude`
+---@field line integer
+---@field env boolean|nil Comes from an environment variable, can't be edited
+
+---@class NekifochConfLine
+---@field kind "'blank'"|"'comment'"|"'directive'"
+---@field text string The line as written
+---@field indent string|nil
+---@field option string|nil Also set for commented out directives
+---@field separator string|nil Whitespace between the option and its value
+---@field value string|nil
+
+---@class NekifochDiagnostic
+---@field file string
+---@field line integer
+---@field message string
+
+---Split a kitty.conf line into its parts, keeping enough to write it back
+---the way it was formatted
+---@param text string
+---@return NekifochConfLine
+M.parse_line = function(text)
+  if text:match('^%s*$') then return { kind = 'blank', text = text } end
+
+  local commented = text:match('^%s*#%s*(.-)%s*$')
+  if commented then
+    -- Commented out defaults like `# font_size 11.0`
+    local option, separator, value = commented:match('^(%a[%w_]*)(%s+)(.*)$')
+    return {
+      kind = 'comment',
+      text = text,
+      option = option,
+      separator = separator,
+      value = value,
+    }
+  end
+
+  local indent, option, separator, value =
+    text:match('^(%s*)(%S+)(%s*)(.-)%s*$')
+  return {
+    kind = 'directive',
+    text = text,
+    indent = indent,
+    option = option,
+    separator = separator,
+    value = value,
+  }
+end
+
+---Text of `line` set to `value`, keeping its indentation and alignment
+---@param line NekifochConfLine
+---@param value string
+---@return string
+M.format = function(line, value)
+  local separator = line.separator ~= '' and line.separator or ' '
+  return (line.indent or '') .. line.option .. separator .. value
+end
+
+---@param path string
+---@return NekifochConfLine[]
+M.read = function(path)
+  return vim.tbl_map(M.parse_line, vim.split(util.read_file(path), '\n'))
+end
+
+---@param path string
+---@param lines NekifochConfLine[]
+M.write = function(path, lines)
+  local text = vim.tbl_map(function(line) return line.text end, lines)
+  util.write_file(path, table.concat(text, '\n'))
+end
+
+---Kitty resolves relative includes against the config directory
+---@param dir string
+---@param path string
+---@return string
+local function resolve(dir, path)
+  path = vim.fn.expand(path)
+  if path:sub(1, 1) ~= '/' then path = dir .. '/' .. path end
+  return vim.fn.simplify(path)
+end
+
+---@class NekifochParseState
+---@field dir string
+---@field out NekifochDirective[]
+---@field seen table<string, boolean>
+---@field diagnostics NekifochDiagnostic[]
+
+local parse
+
+---@param file string
+---@param state NekifochParseState
+local function include(file, state)
+  if state.seen[file] or vim.fn.filereadable(file) == 0 then return end
+  state.seen[file] = true
+  parse(util.read_file(file), file, state)
+end
+
+---@param text string
+---@param source string
+---@param state NekifochParseState
+---@param env boolean|nil
+parse = function(text, source, state, env)
+  local lnum = 0
+  for text_line in (text .. '\n'):gmatch('([^\n]*)\n') do
+    lnum = lnum + 1
+    local line = M.parse_line(text_line)
+    local option, value = line.option, line.value
+    local function report(message)
+      table.insert(
+        state.diagnostics,
+        { file = source, line = lnum, message = message }
+      )
+    end
+
+    if line.kind ~= 'directive' then
+      -- Comments and blank lines carry no settings
+    elseif value == '' then
+      report(option .. ' has no value')
+    elseif option == 'include' then
+      local file = resolve(state.dir, value)
+      if vim.fn.filereadable(file) == 0 then
+        report('Included file not found: ' .. file)
+      end
+      include(file, state)
+    elseif option == 'globinclude' then
+      local files = vim.fn.glob(resolve(state.dir, value), false, true)
+      table.sort(files)
+      for _, file in ipairs(files) do
+        include(file, state)
+      end
+    elseif option == 'envinclude' then
+      local pattern = vim.fn.glob2regpat(value)
+      local names = vim.tbl_keys(vim.fn.environ())
+      table.sort(names)
+      for _, name in ipairs(names) do
+        if vim.fn.match(name, pattern) ~= -1 then
+          parse(vim.env[name], '$' .. name, state, true)
+        end
+      end
+    else
+      table.insert(state.out, {
+        option = option,
+        value = value,
+        file = source,
+        line = lnum,
+        env = env,
+      })
+    end
+  end
+end
+
+---@param path string Main kitty.conf
+---@return NekifochParseState
+local function parse_all(path)
+  local state = {
+    dir = vim.fn.fnamemodify(path, ':h'),
+    out = {},
+    seen = {},
+    diagnostics = {},
+  }
+  include(vim.fn.simplify(path), state)
+  return state
+end
+
+---Every directive of `path` and the files it includes, in the order kitty
+---reads them
+---@param path string Main kitty.conf
+---@return NekifochDirective[]
+M.directives = function(path) return parse_all(path).out end
+
+---Directive that is in effect for every option, the last one wins
+---@param path string Main kitty.conf
+---@return table<string, NekifochDirective>
+---@return string[] files Every file read, includes too
+M.effective = function(path)
+  local state = parse_all(path)
+  local settings = {}
+  for _, directive in ipairs(state.out) do
+    settings[directive.option] = directive
+  end
+  return settings, vim.tbl_keys(state.seen)
+end
+
+-- Options kitty accumulates instead of overriding
+local repeatable = {
+  map = true,
+  mouse_map = true,
+  symbol_map = true,
+  narrow_symbols = true,
+  font_features = true,
+  env = true,
+  action_alias = true,
+  kitten_alias = true,
+  modify_font = true,
+}
+
+---Problems found while reading `path` and its includes, including settings
+---a later line overrides
+---@param path string Main kitty.conf
+---@return NekifochDiagnostic[]
+M.diagnostics = function(path)
+  local state = parse_all(path)
+  local last = {}
+  for _, directive in ipairs(state.out) do
+    local previous = last[directive.option]
+    if previous and not repeatable[directive.option] then
+      table.insert(state.diagnostics, {
+        file = previous.file,
+        line = previous.line,
+        message = directive.option
+          .. ' is overridden in '
+          .. vim.fn.fnamemodify(directive.file, ':~')
+          .. ':'
+          .. directive.line,
+      })
+    end
+    last[directive.option] = directive
+  end
+  return state.diagnostics
+end
+
+---@class NekifochFontSpec
+---@field extended boolean Written as `family="..." style="..."`
+---@field fields string[][] `{ key, value }` pairs in the order written
+
+---Parse a font option value, either a plain family name or the extended
+---`family="Fira Code" style="Medium" features="+ss01"` form of kitty 0.30
+---@param value string
+---@return NekifochFontSpec
+M.parse_font = function(value)
+  if not value:match('^%s*%a[%w_]*=') then
+    return { extended = false, fields = { { 'family', value } } }
+  end
+
+  local fields, pos = {}, 1
+  while true do
+    local _, key_end, key = value:find('^%s*(%a[%w_]*)=', pos)
+    if not key_end then break end
+    local quote = value:sub(key_end + 1, key_end + 1)
+    local field
+    if quote == '"' or quote == "'" then
+      local close = value:find(quote, key_end + 2, true) or #value + 1
+      field = value:sub(key_end + 2, close - 1)
+      pos = close + 1
+    else
+      field = value:match('^%S*', key_end + 1)
+      pos = key_end + 1 + #field
+    end
+    table.insert(fields, { key, field })
+  end
+  return { extended = true, fields = fields }
+end
+
+---@param spec NekifochFontSpec
+---@param key string
+---@return string|nil
+M.font_field = function(spec, key)
+  for _, field in ipairs(spec.fields) do
+    if field[1] == key then return field[2] end
+  end
+end
+
+---Set `key`, switching to the extended form for anything but the family.
+---An empty value removes the field
+---@param spec NekifochFontSpec
+---@param key string
+---@param value string
+M.set_font_field = function(spec, key, value)
+  if value == '' then
+    for i, field in ipairs(spec.fields) do
+      if field[1] == key then
+        table.remove(spec.fields, i)
+        return
+      end
+    end
+    return
+  end
+  if key ~= 'family' then spec.extended = true end
+  for _, field in ipairs(spec.fields) do
+    if field[1] == key then
+      field[2] = value
+      return
+    end
+  end
+  table.insert(spec.fields, key == 'family' and 1 or #spec.fields + 1, {
+    key,
+    value,
+  })
+end
+
+---@param spec NekifochFontSpec
+---@return string
+M.format_font = function(spec)
+  if not spec.extended then return M.font_field(spec, 'family') or '' end
+  local parts = {}
+  for _, field in ipairs(spec.fields) do
+    local value = field[2]
+    if value == '' or value:find('%s') then value = '"' .. value .. '"' end
+    table.insert(parts, field[1] .. '=' .. value)
+  end
+  return table.concat(parts, ' ')
+end
+
+---Set the directive on line `lnum` of `path` to `value`
+---@param path string
+---@param lnum integer
+---@param value string
+M.set_line = function(path, lnum, value)
+  local lines = M.read(path)
+  lines[lnum].text = M.format(lines[lnum], value)
+  M.write(path, lines)
+end
+
+---Uncomment the first commented out `option` in `path` with `value`
+---@param path string
+---@param option string
+---@param value string
+---@return boolean uncommented
+M.uncomment = function(path, option, value)
+  local lines = M.read(path)
+  for _, line in ipairs(lines) do
+    if line.kind == 'comment' and line.option == option then
+      line.text =
+        M.format({ option = option, separator = line.separator }, value)
+      M.write(path, lines)
+      return true
+    end
+  end
+  return false
+end
+
+---Append `option` to `path`, keeping a trailing newline
+---@param path string
+---@param option string
+---@param value string
+M.append = function(path, option, value)
+  local lines = M.read(path)
+  local line = { kind = 'directive', text = option .. ' ' .. value }
+  if lines[#lines].text == '' then
+    table.insert(lines, #lines, line)
+  else
+    table.insert(lines, line)
+    table.insert(lines, { kind = 'blank', text = '' })
+  end
+  M.write(path, lines)
+end
+
+---Comment out line `lnum` of `path`
+---@param path string
+---@param lnum integer
+M.comment_line = function(path, lnum)
+  local lines = M.read(path)
+  lines[lnum].text = '# ' .. lines[lnum].text
+  M.write(path, lines)
+end
+
+return M
@@ -0,0 +1,50 @@
+-- Log of external commands, file writes and reloads, kept in
+-- stdpath('log')/nekifoch.log to find out why a change did nothing
+local M = {}
+
+M.levels = { debug = 1, info = 2, warn = 3, error = 4 }
+
+---@return string
+M.file = function() return vim.fn.stdpath('log') .. '/nekifoch.log' end
+
+---@param level "'debug'"|"'info'"|"'warn'"|"'error'"
+---@param message string
+local function write(level, message)
+  -- `off` or an unknown level logs nothing
+  local configured = M.levels[require('nekifoch').config.log.level]
+  if not configured or M.levels[level] < configured then return end
+
+  vim.fn.mkdir(vim.fn.fnamemodify(M.file(), ':h'), 'p')
+  local f = io.open(M.file(), 'a')
+  if not f then return end
+  f:write(
+    os.date('%Y-%m-%d %H:%M:%S')
+      .. ' ['
+      .. level:upper()
+      .. '] '
+      .. message
+      .. '\n'
+  )
+  f:close()
+end
+
+---@param message string
+M.debug = function(message) write('debug', message) end
+
+---@param message string
+M.info = function(message) write('info', message) end
+
+---@param message string
+M.warn = function(message) write('warn', message) end
+
+---@param message string
+M.error = function(message) write('error', message) end
+
+---Command as it would be typed
+---@param cmd string[]
+---@return string
+M.format_cmd = function(cmd)
+  return table.concat(vim.tbl_map(vim.fn.shellescape, cmd), ' ')
+end
+
+return M
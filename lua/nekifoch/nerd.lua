@@ -0,0 +1,89 @@
+-- Nerd Fonts among the installed families, known by their name or by
+-- having powerline and icon glyphs
+local util = require('nekifoch.utils')
+
+local M = {}
+
+---Families with the glyphs, looked up once per session
+---@type table<string, boolean>|nil
+local probed = nil
+
+---Look the glyphs up again, e.g. after fonts were installed
+M.reset = function() probed = nil end
+
+-- Powerline separator and a Font Awesome glyph, like `font_info`
+local probe_cmd = { 'fc-list', ':charset=e0b0 f013', 'family' }
+
+---@param output string
+---@return table<string, boolean>
+local function parse(output)
+  local families = {}
+  for line in output:gmatch('[^\r\n]+') do
+    -- Families with localized names are listed comma separated
+    for _, family in ipairs(vim.split(line, ',', { plain = true })) do
+      families[vim.trim(family)] = true
+    end
+  end
+  return families
+end
+
+---Whether the glyphs were looked up, until then only names tell
+---@return boolean
+M.probed = function() return probed ~= nil end
+
+---Look the glyphs up, blocking the editor
+---@return table<string, boolean>
+M.probe = function()
+  if probed then return probed end
+  probed = {}
+  if vim.fn.executable('fc-list') == 0 then return probed end
+  probed = parse(util.system(probe_cmd))
+  return probed
+end
+
+local loading = false
+
+---@type fun()[]
+local waiting = {}
+
+---Look the glyphs up in the background, `callback` runs once they are known
+---@param callback fun()|nil
+M.probe_async = function(callback)
+  if probed then
+    if callback then callback() end
+    return
+  end
+  if callback then table.insert(waiting, callback) end
+  if loading then return end
+  loading = true
+
+  local function done(output)
+    loading = false
+    probed = probed or parse(output)
+    local callbacks = waiting
+    waiting = {}
+    for _, cb in ipairs(callbacks) do
+      cb()
+    end
+  end
+  if vim.fn.executable('fc-list') == 0 then return done('') end
+  util.read_async(probe_cmd, done)
+end
+
+---Known by its name, or by its glyphs once they were looked up
+---@param family string
+---@return boolean
+M.is_nerd = function(family)
+  return family:find('Nerd Font', 1, true) ~= nil
+    or family:match(' NF[MP]?$') ~= nil
+    or (probed or {})[family] == true
+end
+
+---@param fonts string[]
+---@return string[]
+M.filter = function(fonts)
+  M.probe()
+  return vim.tbl_filter(M.is_nerd, fonts)
+end
+
+return M
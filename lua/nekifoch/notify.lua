@@ -0,0 +1,36 @@
+-- User facing messages. They go through vim.notify, so nvim-notify or
+-- fidget show them, or are echoed into :messages with `notify = false`
+local M = {}
+
+local levels = vim.log.levels
+
+local groups = {
+  [levels.WARN] = 'WarningMsg',
+  [levels.ERROR] = 'ErrorMsg',
+}
+
+---@param message string|string[] Lines are sent as one message
+---@param level integer `vim.log.levels`
+---@param title string|nil
+M.send = function(message, level, title)
+  if type(message) == 'table' then message = table.concat(message, '\n') end
+  if require('nekifoch').config.notify then
+    vim.notify(message, level, { title = title or 'Nekifoch' })
+  else
+    vim.api.nvim_echo({ { message, groups[level] } }, true, {})
+  end
+end
+
+---@param message string|string[]
+---@param title string|nil
+M.info = function(message, title) M.send(message, levels.INFO, title) end
+
+---@param message string|string[]
+---@param title string|nil
+M.warn = function(message, title) M.send(message, levels.WARN, title) end
+
+---@param message string|string[]
+---@param title string|nil
+M.error = function(message, title) M.send(message, levels.ERROR, title) end
+
+return M
@@ -0,0 +1,66 @@
+local ui = require('nekifoch.ui')
+
+---@param lines string[] Diff to confirm
+---@param on_confirm fun()
+---@param on_cancel fun()
+return function(lines, on_confirm, on_cancel)
+  local Popup = require('nui.popup')
+  local event = require('nui.utils.autocmd').event
+
+  local width = 40
+  for _, line in ipairs(lines) do
+    width = math.max(width, vim.fn.strdisplaywidth(line))
+  end
+  local layout = ui.layout(math.min(width, vim.o.columns - 8), #lines)
+  local popup = Popup({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    enter = true,
+    focusable = true,
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('info'),
+      text = {
+        top = ui.title(' Write changes? '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({ 'y write', 'n cancel', { 'back', 'back' } }),
+        }),
+      },
+    },
+    win_options = ui.win_options(),
+  })
+
+  ui.mount(popup)
+  vim.api.nvim_buf_set_lines(popup.bufnr, 0, -1, false, lines)
+  for option, value in pairs(ui.buf_options()) do
+    vim.bo[popup.bufnr][option] = value
+  end
+  vim.bo[popup.bufnr].filetype = 'diff'
+
+  -- Leaving the window any other way counts as cancelling
+  local done = false
+  ---@param callback fun()
+  local function finish(callback)
+    if done then return end
+    done = true
+    popup:unmount()
+    ui.reset()
+    callback()
+  end
+
+  popup:on(event.BufLeave, function() finish(on_cancel) end)
+  local opts = { noremap = true, nowait = true }
+  popup:map('n', 'y', function() finish(on_confirm) end, opts)
+  popup:map('n', 'n', function() finish(on_cancel) end, opts)
+  ui.map(popup, 'n', 'confirm', function() finish(on_confirm) end)
+  ui.map(popup, 'n', 'close', function() finish(on_cancel) end)
+  ui.map(popup, 'n', 'back', function()
+    if #ui.stack == 0 then return finish(on_cancel) end
+    done = true
+    popup:unmount()
+    ui.back()
+  end)
+end
@@ -0,0 +1,109 @@
+local util = require('nekifoch.utils')
+local features = require('nekifoch.features')
+local ui = require('nekifoch.ui')
+
+local marks = { [true] = '[+]', [false] = '[-]' }
+
+return function()
+  local Menu = require('nui.menu')
+  local event = require('nui.utils.autocmd').event
+
+  local current = require('nekifoch.backend').current().get()
+  local applied = current.spec and current.spec.features or ''
+
+  ---Features of the config first, then the known ones left unset
+  ---@type { name: string, enabled: boolean|nil }[]
+  local list = features.parse(applied)
+  local listed = {}
+  for _, feature in ipairs(list) do
+    listed[feature.name] = true
+  end
+  for _, name in ipairs(features.known) do
+    if not listed[name] then table.insert(list, { name = name }) end
+  end
+
+  ---@return NuiTree.Node[]
+  local function items()
+    return vim.tbl_map(
+      function(feature)
+        return Menu.item(
+          (marks[feature.enabled] or '[ ]') .. ' ' .. feature.name,
+          { feature = feature }
+        )
+      end,
+      list
+    )
+  end
+
+  ---@param text string
+  local function apply(text)
+    if text == applied then return end
+    local command = require('nekifoch.command')
+    local _, err = command.run('features', { text ~= '' and text or 'none' })
+    if not err then applied = text end
+  end
+  -- Toggles only update the window, the config is written once they stop
+  local schedule =
+    util.debounce(require('nekifoch').config.size_debounce, apply)
+
+  local layout = ui.layout(30, #list)
+  local menu = Menu({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    buf_options = ui.buf_options(),
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('picker'),
+      text = {
+        top = ui.title(' Font features '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({ { 'confirm', 'toggle' }, { 'close', 'quit' } }),
+          current.font,
+        }),
+      },
+    },
+    win_options = ui.win_options(),
+  }, {
+    lines = items(),
+    keymap = vim.tbl_extend('force', ui.menu_keymap(), { submit = {} }),
+  })
+
+  ---Cycle the feature under the cursor from unset to enabled to disabled
+  local function toggle()
+    local node = menu.tree:get_node()
+    if not node then return end
+    local feature = node.feature
+    if feature.enabled == nil then
+      feature.enabled = true
+    elseif feature.enabled then
+      feature.enabled = false
+    else
+      feature.enabled = nil
+    end
+
+    local lnum = vim.api.nvim_win_get_cursor(menu.winid)[1]
+    menu.tree:set_nodes(items())
+    menu.tree:render()
+    vim.api.nvim_win_set_cursor(menu.winid, { lnum, 0 })
+    local set = vim.tbl_filter(
+      function(item) return item.enabled ~= nil end,
+      list
+    )
+    schedule(features.format(set))
+  end
+
+  menu:on(event.BufLeave, function() menu:unmount() end)
+  ui.map(menu, 'n', 'confirm', toggle)
+  ui.map(menu, 'n', 'back', function()
+    menu:unmount()
+    ui.back()
+  end)
+  ui.map(menu, 'n', 'close', function()
+    menu:unmount()
+    ui.reset()
+  end)
+  ui.mount(menu)
+end
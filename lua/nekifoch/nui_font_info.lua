@@ -0,0 +1,77 @@
+local ui = require('nekifoch.ui')
+
+---Details of `family` next to the window `winid`
+---@param family string
+---@param winid integer
+---@param on_close fun() Called once the details are closed
+return function(family, winid, on_close)
+  local Popup = require('nui.popup')
+  local event = require('nui.utils.autocmd').event
+  local command = require('nekifoch.command')
+
+  local info, err = command.execute('font_info', { family })
+  if not info then
+    require('nekifoch.notify').error(err)
+    return on_close()
+  end
+  local lines = command.font_info_lines(info)
+  table.remove(lines, 1)
+
+  local width = 30
+  for _, line in ipairs(lines) do
+    width = math.max(width, vim.fn.strdisplaywidth(line))
+  end
+  local popup = Popup({
+    relative = { type = 'win', winid = winid },
+    position = { row = -1, col = vim.api.nvim_win_get_width(winid) + 3 },
+    size = {
+      width = math.min(width, vim.o.columns - 8),
+      height = ui.height(#lines),
+    },
+    zindex = 60,
+    enter = true,
+    focusable = true,
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('info'),
+      text = {
+        top = ui.title(' ' .. family .. ' '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({ { 'coverage', 'coverage' }, { 'back', 'back' } }),
+        }),
+      },
+    },
+    win_options = ui.win_options(),
+  })
+
+  popup:mount()
+  vim.api.nvim_buf_set_lines(popup.bufnr, 0, -1, false, lines)
+  for option, value in pairs(ui.buf_options()) do
+    vim.bo[popup.bufnr][option] = value
+  end
+
+  local closed = false
+  local function close()
+    if closed then return end
+    closed = true
+    popup:unmount()
+    on_close()
+  end
+  popup:on(event.BufLeave, close)
+  ui.map(popup, 'n', 'back', close)
+  ui.map(popup, 'n', 'close', close)
+  ui.map(popup, 'n', 'details', close)
+  ui.map(popup, 'n', 'coverage', function()
+    close()
+    vim.ui.input({ prompt = 'Glyphs to check: ' }, function(text)
+      if not text or text == '' then return end
+      local func = require('nekifoch.command_func')
+      local coverage, coverage_err = func.coverage({ text }, family)
+      if not coverage then
+        return require('nekifoch.notify').error(coverage_err)
+      end
+      command.formatters.coverage(coverage)
+    end)
+  end)
+end
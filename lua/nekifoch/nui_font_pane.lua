@@ -0,0 +1,94 @@
+-- Pane beside the font window following the highlighted family with its
+-- styles and the sample line, rendered in the family with `preview.images`
+local util = require('nekifoch.utils')
+local ui = require('nekifoch.ui')
+
+---@class NekifochFontPane
+---@field update fun(family: string|nil) Show `family`, nothing when `nil`
+---@field unmount fun()
+
+---@param winid integer Font window the pane sits next to
+---@param height integer
+---@return NekifochFontPane
+return function(winid, height)
+  local Popup = require('nui.popup')
+  local command = require('nekifoch.command')
+  local preview = require('nekifoch.preview')
+  local options = require('nekifoch').config.preview
+  local images = options.images
+  if images then
+    local err = preview.images_unavailable()
+    if err then
+      require('nekifoch.log').warn(err)
+      images = false
+    end
+  end
+
+  local pane = Popup({
+    relative = { type = 'win', winid = winid },
+    position = { row = -1, col = vim.api.nvim_win_get_width(winid) + 3 },
+    size = { width = 40, height = height },
+    focusable = false,
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('info'),
+      text = { top = ui.title(' Details '), top_align = 'center' },
+    },
+    win_options = ui.win_options(),
+  })
+  pane:mount()
+  for option, value in pairs(ui.buf_options()) do
+    vim.bo[pane.bufnr][option] = value
+  end
+
+  ---@param lines string[]
+  local function show(lines)
+    if not pane.bufnr then return end
+    vim.bo[pane.bufnr].modifiable = true
+    vim.api.nvim_buf_set_lines(pane.bufnr, 0, -1, false, lines)
+    vim.bo[pane.bufnr].modifiable = false
+  end
+
+  ---File of the regular face, the first one without it
+  ---@param info NekifochFontInfo
+  ---@return string
+  local function regular(info)
+    for _, face in ipairs(info.faces) do
+      if face.style == 'Regular' then return face.file end
+    end
+    return info.faces[1].file
+  end
+
+  -- Lines the rendered sample covers
+  local image_rows = 3
+
+  -- Looking a family up runs fc-list, wait for the cursor to settle
+  local update, cancel = util.debounce(100, function(family)
+    if images then preview.clear() end
+    if not family then return show({}) end
+    local info, err = command.execute('font_info', { family })
+    if not info then return show({ err }) end
+    local lines = command.font_info_lines(info)
+    table.remove(lines, 1)
+    local png = images and preview.render(regular(info))
+    for i = 1, png and image_rows or 1 do
+      table.insert(lines, i, png and '' or options.sample)
+    end
+    table.insert(lines, (png and image_rows or 1) + 1, '')
+    pane.border:set_text('top', ui.title(' ' .. family .. ' '), 'center')
+    show(lines)
+    if png and pane.winid then
+      vim.cmd('redraw')
+      preview.show(png, pane.winid, 1, image_rows)
+    end
+  end)
+
+  return {
+    update = update,
+    unmount = function()
+      cancel()
+      if images then preview.clear() end
+      pane:unmount()
+    end,
+  }
+end
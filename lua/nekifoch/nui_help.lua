@@ -0,0 +1,49 @@
+local ui = require('nekifoch.ui')
+
+---@param lines string[]
+return function(lines)
+  local Popup = require('nui.popup')
+  local event = require('nui.utils.autocmd').event
+
+  local width = 40
+  for _, line in ipairs(lines) do
+    width = math.max(width, vim.fn.strdisplaywidth(line))
+  end
+  local layout = ui.layout(math.min(width + 1, vim.o.columns - 8), #lines)
+  local popup = Popup({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    enter = true,
+    focusable = true,
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('info'),
+      text = {
+        top = ui.title(' Nekifoch help '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({ { 'next', 'scroll' }, { 'close', 'quit' } }),
+        }),
+      },
+    },
+    win_options = ui.win_options(),
+  })
+
+  ui.mount(popup)
+  vim.api.nvim_buf_set_lines(popup.bufnr, 0, -1, false, lines)
+  for option, value in pairs(ui.buf_options()) do
+    vim.bo[popup.bufnr][option] = value
+  end
+
+  popup:on(event.BufLeave, function() popup:unmount() end)
+  ui.map(popup, 'n', 'back', function()
+    popup:unmount()
+    ui.back()
+  end)
+  ui.map(popup, 'n', 'close', function()
+    popup:unmount()
+    ui.reset()
+  end)
+end
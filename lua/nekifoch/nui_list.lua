@@ -0,0 +1,138 @@
+local ui = require('nekifoch.ui')
+
+---@param fonts string[]
+return function(fonts)
+  local Popup = require('nui.popup')
+  local event = require('nui.utils.autocmd').event
+
+  local ok, current = pcall(require('nekifoch.backend').current().get)
+  local curFont = ok and current.font or nil
+
+  local width = 0
+  for _, font in ipairs(fonts) do
+    width = math.max(width, vim.fn.strdisplaywidth(font))
+  end
+  -- Marker, name and the gap to the next column
+  local cell = width + 4
+
+  ---Font shown at each line and column, to apply the one under the cursor
+  ---@type string[][]
+  local grid
+  ---@type string[]
+  local lines
+
+  ---As many columns as fit the editor and `window.list_max_width`
+  ---@return integer
+  local function column_count()
+    local max = require('nekifoch').config.window.list_max_width
+    local available = math.min(max, vim.o.columns - 8)
+    return math.max(1, math.min(math.floor(available / cell), #fonts))
+  end
+
+  ---Lay the fonts out in `columns`, row by row
+  ---@param columns integer
+  local function flow(columns)
+    grid, lines = {}, {}
+    for i, font in ipairs(fonts) do
+      local row = math.floor((i - 1) / columns) + 1
+      grid[row] = grid[row] or {}
+      table.insert(grid[row], font)
+      local text = (font == curFont and '● ' or '- ') .. font
+      lines[row] = (lines[row] or '')
+        .. text
+        .. string.rep(' ', cell - vim.fn.strdisplaywidth(text))
+    end
+    lines = vim.tbl_map(
+      function(line) return (line:gsub('%s+$', '')) end,
+      lines
+    )
+    if #lines == 0 then lines = { 'No fonts found' } end
+  end
+
+  local columns = column_count()
+  flow(columns)
+
+  local layout = ui.layout(cell * columns, #lines)
+  local popup = Popup({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    enter = true,
+    focusable = true,
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('picker'),
+      text = {
+        top = ui.title(' Available fonts '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({ { 'confirm', 'apply' }, { 'close', 'quit' } }),
+        }),
+      },
+    },
+    win_options = ui.win_options(),
+  })
+
+  ui.mount(popup)
+  ---@param bufnr integer
+  local function show(bufnr)
+    vim.bo[bufnr].modifiable = true
+    vim.api.nvim_buf_set_lines(bufnr, 0, -1, false, lines)
+    for option, value in pairs(ui.buf_options()) do
+      vim.bo[bufnr][option] = value
+    end
+  end
+  show(popup.bufnr)
+
+  -- Re-flow the fonts when the editor is resized, keeping the cursor on the
+  -- same font. The autocmd removes itself once the window is gone
+  local group = vim.api.nvim_create_augroup('NekifochList', { clear = true })
+  vim.api.nvim_create_autocmd('VimResized', {
+    group = group,
+    callback = function()
+      if not popup.winid then return true end
+      local count = column_count()
+      if count == columns then return end
+      local cursor = vim.api.nvim_win_get_cursor(popup.winid)
+      local index = (cursor[1] - 1) * columns + 1
+      columns = count
+      flow(columns)
+      local resized = ui.layout(cell * columns, #lines)
+      popup:update_layout({
+        relative = resized.relative,
+        position = resized.position,
+        size = resized.size,
+      })
+      show(popup.bufnr)
+      local row = math.floor((index - 1) / columns) + 1
+      vim.api.nvim_win_set_cursor(popup.winid, { math.min(row, #lines), 0 })
+    end,
+  })
+
+  ---Font of the column under the cursor
+  ---@return string|nil
+  local function under_cursor()
+    local cursor = vim.api.nvim_win_get_cursor(popup.winid)
+    local line = vim.api.nvim_get_current_line():sub(1, cursor[2])
+    local column = math.floor(vim.fn.strdisplaywidth(line) / cell) + 1
+    return (grid[cursor[1]] or {})[column]
+  end
+
+  popup:on(event.BufLeave, function() popup:unmount() end)
+  ui.map(popup, 'n', 'confirm', function()
+    local font = under_cursor()
+    if not font then return end
+    popup:unmount()
+    ui.reset()
+    require('nekifoch.command').run('set_font', { font })
+  end)
+  ui.map(popup, 'n', 'back', function()
+    popup:unmount()
+    ui.back()
+  end)
+  ui.map(popup, 'n', 'close', function()
+    popup:unmount()
+    ui.reset()
+  end)
+end
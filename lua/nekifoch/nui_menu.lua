@@ -0,0 +1,53 @@
+---@param items NekifochMenuItem[]
+---@param title string|nil
+---@param reopen fun()|nil Return here with `back` after choosing an item
+return function(items, title, reopen)
+  local Menu = require('nui.menu')
+  local event = require('nui.utils.autocmd').event
+  local ui = require('nekifoch.ui')
+
+  local menuItems = {}
+  for _, item in ipairs(items) do
+    table.insert(menuItems, Menu.item(item.label, { action = item.action }))
+  end
+
+  local layout = ui.layout(30, #menuItems)
+  local menu = Menu({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    buf_options = ui.buf_options(),
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('menu'),
+      text = {
+        top = ui.title(' ' .. (title or 'Nekifoch') .. ' '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({ { 'confirm', 'select' }, { 'close', 'quit' } }),
+        }),
+      },
+    },
+    win_options = ui.win_options(),
+  }, {
+    lines = menuItems,
+    keymap = ui.menu_keymap(),
+    -- Let the menu close before the next window opens
+    on_submit = function(item)
+      if reopen then ui.push(reopen) end
+      vim.schedule(item.action)
+    end,
+  })
+
+  menu:on(event.BufLeave, function() menu:unmount() end)
+  ui.map(menu, 'n', 'back', function()
+    menu:unmount()
+    ui.back()
+  end)
+  ui.map(menu, 'n', 'close', function()
+    menu:unmount()
+    ui.reset()
+  end)
+  ui.mount(menu)
+end
@@ -0,0 +1,100 @@
+local util = require('nekifoch.utils')
+local ui = require('nekifoch.ui')
+
+-- Settings that scale the cell start from 100%, offsets from 0
+local percent = {
+  cell_height = true,
+  cell_width = true,
+  underline_thickness = true,
+  strikethrough_thickness = true,
+}
+
+---@param what string `modify_font` setting, e.g. `cell_height`
+return function(what)
+  local Input = require('nui.input')
+  local event = require('nui.utils.autocmd').event
+
+  local config = require('nekifoch').config
+  local applied = util.get_modify_font(what) or ''
+
+  ---@param value string
+  local function apply(value)
+    value = vim.trim(value)
+    if value == '' or value == applied then return end
+    local command = require('nekifoch.command')
+    local _, err = command.run('modify', { what, value })
+    if not err then applied = value end
+  end
+  -- Steps only update the window, the config is written once they stop
+  local schedule, cancel = util.debounce(config.size_debounce, apply)
+
+  local layout = ui.layout(32)
+  local input = Input({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    border = {
+      style = ui.border('input'),
+      padding = { 0, 1 },
+      text = {
+        top = ui.title(' ' .. what:gsub('_', ' ') .. ' '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({
+            '<CR> apply',
+            { { 'size_up', 'size_down' }, 'step' },
+            { 'close', 'quit' },
+          }),
+        }),
+      },
+    },
+    buf_options = ui.buf_options(true),
+    win_options = ui.win_options(),
+  }, {
+    prompt = '',
+    default_value = applied,
+    on_submit = function(value)
+      ui.reset()
+      cancel()
+      apply(value)
+    end,
+  })
+
+  ---Percentages step by 5%, points and pixels by 1, keeping the unit
+  ---@param direction integer
+  local function step(direction)
+    local line = vim.api.nvim_buf_get_lines(input.bufnr, 0, 1, false)[1]
+    local number, unit = line:match('^%s*([+-]?[%d%.]+)(%S*)')
+    number = tonumber(number)
+    if not number then
+      number, unit = percent[what] and 100 or 0, percent[what] and '%' or ''
+    end
+    local value = number + direction * (unit == '%' and 5 or 1)
+    if unit == '%' then value = math.max(value, 5) end
+    local text = tostring(value) .. unit
+    vim.api.nvim_buf_set_lines(input.bufnr, 0, 1, false, { text })
+    vim.api.nvim_win_set_cursor(input.winid, { 1, #text })
+    schedule(text)
+  end
+
+  input:on(event.BufLeave, function() input:unmount() end)
+  -- A count steps several times, written once
+  ui.map(input, 'n', 'size_up', function() step(vim.v.count1) end)
+  ui.map(input, 'n', 'size_down', function() step(-vim.v.count1) end)
+  ui.map(input, 'i', 'size_up', function() step(1) end)
+  ui.map(input, 'i', 'size_down', function() step(-1) end)
+  local function back()
+    input:unmount()
+    ui.back()
+  end
+  local function quit()
+    input:unmount()
+    ui.reset()
+  end
+  ui.map(input, 'n', 'back', back)
+  ui.map(input, 'i', 'back', back)
+  ui.map(input, 'n', 'close', quit)
+  ui.map(input, 'i', 'close', quit)
+  ui.mount(input)
+end
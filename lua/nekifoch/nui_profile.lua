@@ -0,0 +1,66 @@
+---@param profile NekifochProfile
+---@return string
+local function describe(profile)
+  local parts = {}
+  if profile.family then table.insert(parts, profile.family) end
+  if profile.size then table.insert(parts, tostring(profile.size)) end
+  return table.concat(parts, ', ')
+end
+
+return function()
+  local Menu = require('nui.menu')
+  local event = require('nui.utils.autocmd').event
+  local ui = require('nekifoch.ui')
+  local profiles = require('nekifoch').config.profiles
+
+  local names = vim.tbl_keys(profiles)
+  table.sort(names)
+
+  local profileMenuItems = {}
+  for _, name in ipairs(names) do
+    table.insert(
+      profileMenuItems,
+      Menu.item(name .. ' │ ' .. describe(profiles[name]), { name = name })
+    )
+  end
+
+  local layout = ui.layout(40, #names)
+  local menu = Menu({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    buf_options = ui.buf_options(),
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('menu'),
+      text = {
+        top = ui.title(' Font profile '),
+        top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({ { 'confirm', 'select' }, { 'close', 'quit' } }),
+        }),
+      },
+    },
+    win_options = ui.win_options(),
+  }, {
+    lines = profileMenuItems,
+    max_width = 20,
+    keymap = ui.menu_keymap(),
+    on_submit = function(item)
+      ui.reset()
+      require('nekifoch.command').run('profile', { item.name })
+    end,
+  })
+
+  menu:on(event.BufLeave, function() menu:unmount() end)
+  ui.map(menu, 'n', 'back', function()
+    menu:unmount()
+    ui.back()
+  end)
+  ui.map(menu, 'n', 'close', function()
+    menu:unmount()
+    ui.reset()
+  end)
+  ui.mount(menu)
+end
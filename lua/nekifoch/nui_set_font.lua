@@ -1,69 +1,344 @@
-return function()
+---@param style NekifochStyle|nil
+---@return string
+local function title(style)
+  if not style then return ' Set font family ' end
+  return ' Set ' .. style:gsub('_', ' ') .. ' font '
+end
+
+---@param compatibleFonts string[]
+---@param style NekifochStyle|nil Pick the bold/italic font instead
+---@param sections boolean Show recent fonts above the list
+---@param on_select fun(font: string)|nil Called instead of applying the font
+local function open(compatibleFonts, style, sections, on_select)
   local Menu = require('nui.menu')
   local event = require('nui.utils.autocmd').event
-  local util = require('nekifoch.utils')
+  local ui = require('nekifoch.ui')
+  local favorites = require('nekifoch.favorites')
+  local terminal = require('nekifoch.backend').current()
 
-  local fontMenuItems = {}
-  local _, compatibleFonts =
-    util.compareFontsWithKittyListFonts(util.getCachedInstalledFonts())
-  for _, font in ipairs(compatibleFonts) do
-    table.insert(fontMenuItems, Menu.item(font))
+  local nerd = require('nekifoch.nerd')
+  local query, nerd_only = '', false
+
+  ---Fonts containing the filter query, favorites first, below the recent
+  ---ones until filtering
+  ---@return NuiTree.Node[]
+  local function items()
+    local result = {}
+    local show = require('nekifoch').config.recent.show
+    local recent = vim.list_slice(require('nekifoch.recent').fonts(), 1, show)
+    if sections and query == '' and #recent > 0 then
+      table.insert(result, Menu.separator('Recent'))
+      for _, font in ipairs(recent) do
+        table.insert(result, Menu.item(font))
+      end
+      table.insert(result, Menu.separator('All'))
+    end
+    for _, font in ipairs(favorites.first(compatibleFonts)) do
+      local shown = not nerd_only or nerd.is_nerd(font)
+      if shown and font:lower():find(query:lower(), 1, true) then
+        table.insert(result, Menu.item(font))
+      end
+    end
+    -- The menu can't be empty, keep a line that selects nothing
+    if #result == 0 then
+      table.insert(result, Menu.item('No matching fonts', { empty = true }))
+    end
+    return result
   end
 
+  local fontMenuItems = items()
+
+  local ok, current = pcall(terminal.get)
   ---@type string
-  local curFont = util.get().font
-
-  local menu = Menu({
-    -- TODO: change position and size
-    position = '50%',
-    size = {
-      width = 40,
-      height = 10,
-    },
-    buf_options = { filetype = 'nekifoch' },
+  local curFont = ok and current[style or 'font'] or ''
+
+  local live = require('nekifoch').config.live_preview
+    and terminal.preview_font ~= nil
+    and not style
+  -- `overlay` is set while the filter input or the details have focus
+  local mounted, previewing, overlay = false, false, false
+
+  -- Leaving without confirming restores the font from the config
+  local function restore()
+    if previewing then
+      previewing = false
+      terminal.cancel_preview()
+    end
+  end
+
+  local menu
+  ---@type NekifochFontPane|nil
+  local pane
+
+  local preview = require('nekifoch.preview')
+  -- Whether a kitty window follows the highlighted family
+  local windowed = false
+  -- Starting kitty takes a while, wait for the cursor to settle
+  local follow = require('nekifoch.utils').debounce(200, function(family)
+    if not windowed then return end
+    local ok, err = preview.window(family)
+    if not ok then require('nekifoch.notify').error(err) end
+  end)
+
+  local hints = ui.hints({
+    { 'confirm', 'apply' },
+    { 'filter', 'filter' },
+    { 'nerd', 'nerd' },
+    { 'details', 'details' },
+    { 'preview', 'preview' },
+    { 'close', 'quit' },
+  })
+
+  ---Keymap hints, or the current font without them, and the scroll position
+  ---once the list overflows
+  local function footer()
+    return ui.footer({ hints or curFont, ui.position(menu.winid) })
+  end
+
+  local layout = ui.layout(40, #fontMenuItems)
+  menu = Menu({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    buf_options = ui.buf_options(),
     border = {
       padding = { 0, 1 },
-      style = require('nekifoch').config.borders,
+      style = ui.border('picker'),
       text = {
-        top = ' Set font family ',
+        top = ui.title(title(style)),
         top_align = 'center',
-        bottom = ' ' .. curFont .. ' ',
+        bottom = ui.footer({ hints or curFont }),
       },
     },
-    win_options = {
-      winhighlight = 'NormalFloat:NormalFloat,FloatBorder:FloatBorder',
-    },
+    win_options = ui.win_options(),
   }, {
     lines = fontMenuItems,
     max_width = 20,
-    keymap = {
-      focus_next = { 'j', '<Down>', '<Tab>' },
-      focus_prev = { 'k', '<Up>', '<S-Tab>' },
-      close = { '<Esc>', '<C-c>' },
-      submit = { '<CR>', '<Space>' },
-    },
-    -- on_close = function() print('Menu Closed!') end,
+    keymap = ui.menu_keymap(),
+    on_close = restore,
+    on_change = function(item)
+      if mounted then menu.border:set_text('bottom', footer(), 'center') end
+      if live and mounted and not item.empty then
+        previewing = terminal.preview_font(item.text)
+      end
+      local family = item._type ~= 'separator' and not item.empty
+      if pane then pane.update(family and item.text or nil) end
+      if windowed and family then follow(item.text) end
+    end,
     on_submit = function(item)
-      util.replace_font_family(item.text)
-      if vim.fn.systemlist('pidof kitty')[1] ~= '' then
-        vim.cmd('silent !kill -USR1 $(pidof kitty)')
+      ui.reset()
+      if item.empty then return restore() end
+      previewing = false
+      if on_select then
+        return vim.schedule(function() on_select(item.text) end)
       end
+      require('nekifoch.command').run(
+        style and ('set_' .. style .. '_font') or 'set_font',
+        { item.text }
+      )
     end,
   })
 
-  menu:on(event.BufLeave, function() menu:unmount() end)
-  menu:map(
-    'n',
-    { '<Esc>', 'q', '<C-c>' },
-    function() menu:unmount() end,
-    { noremap = true, nowait = true }
-  )
-  menu:map(
-    'i',
-    { '<Esc>', '<C-q>', '<C-c>' },
-    function() menu:unmount() end,
-    { noremap = true, nowait = true }
-  )
+  local function close()
+    restore()
+    windowed = false
+    preview.close_window()
+    if pane then pane.unmount() end
+    menu:unmount()
+  end
+
+  ---Line showing `font`, the first font without one
+  ---@param font string|nil
+  ---@return integer|nil
+  local function find(font)
+    for i = 1, vim.api.nvim_buf_line_count(menu.bufnr) do
+      local node = menu.tree:get_node(i)
+      local item = node and node._type ~= 'separator'
+      if item and (not font or node.text == font) then return i end
+    end
+  end
+
+  ---Mark the configured font, favorites and Nerd Fonts, wherever filtering
+  ---put them
+  local function mark()
+    ui.mark_current(menu.bufnr, find(curFont))
+    local lnums = {}
+    for _, font in ipairs(favorites.list()) do
+      local lnum = find(font)
+      if lnum then table.insert(lnums, lnum) end
+    end
+    ui.mark_favorites(menu.bufnr, lnums)
+
+    local nerd_lnums = {}
+    for i = 1, vim.api.nvim_buf_line_count(menu.bufnr) do
+      local node = menu.tree:get_node(i)
+      local item = node and node._type ~= 'separator' and not node.empty
+      if item and nerd.is_nerd(node.text) then table.insert(nerd_lnums, i) end
+    end
+    ui.mark_nerd(menu.bufnr, nerd_lnums)
+  end
+
+  ---Rebuild the list, keeping the cursor on `font` when it is shown
+  ---@param font string|nil
+  local function render(font)
+    menu.tree:set_nodes(items())
+    menu.tree:render()
+    mark()
+    local lnum = font and find(font) or find() or 1
+    vim.api.nvim_win_set_cursor(menu.winid, { lnum, 0 })
+  end
+
+  ---Show only fonts containing `text`
+  ---@param text string
+  local function filter(text)
+    query = text
+    render()
+  end
+
+  local function toggle_favorite()
+    local node = menu.tree:get_node()
+    if not node or node.empty then return end
+    favorites.toggle(node.text)
+    render(node.text)
+  end
+
+  ---Narrow the list while typing, <CR> applies the selected font
+  local function search()
+    local Input = require('nui.input')
+    overlay = true
+    local input = Input({
+      relative = { type = 'win', winid = menu.winid },
+      position = { row = -3, col = -2 },
+      size = { width = 40 },
+      border = {
+        style = ui.border('input'),
+        padding = { 0, 1 },
+      },
+      buf_options = ui.buf_options(true),
+    }, {
+      prompt = '/',
+      on_change = function(query)
+        if menu.winid then filter(query) end
+      end,
+    })
+
+    ---@param submit boolean
+    local function back(submit)
+      input:unmount()
+      overlay = false
+      if not menu.winid then return end
+      vim.api.nvim_set_current_win(menu.winid)
+      vim.cmd('stopinsert')
+      if submit then
+        local keys = vim.api.nvim_replace_termcodes('<CR>', true, false, true)
+        vim.api.nvim_feedkeys(keys, 'm', false)
+      end
+    end
+
+    input:map('i', '<CR>', function() back(true) end, { noremap = true })
+    input:map('i', '<Esc>', function() back(false) end, { noremap = true })
+    input:on(event.BufLeave, function()
+      if overlay then back(false) end
+    end)
+    input:mount()
+  end
+
+  menu:on(event.BufLeave, function()
+    if not overlay then close() end
+  end)
+  ui.map(menu, 'n', 'filter', search)
+  ui.map(menu, 'n', 'favorite', toggle_favorite)
+  ui.map(menu, 'n', 'details', function()
+    local node = menu.tree:get_node()
+    if not node or node.empty then return end
+    overlay = true
+    require('nekifoch.nui_font_info')(node.text, menu.winid, function()
+      overlay = false
+      if menu.winid then vim.api.nvim_set_current_win(menu.winid) end
+    end)
+  end)
+  ui.map(menu, 'n', 'preview', function()
+    windowed = not windowed
+    local node = menu.tree:get_node()
+    if not windowed then return preview.close_window() end
+    if node and node._type ~= 'separator' and not node.empty then
+      follow(node.text)
+    end
+  end)
+  ui.map(menu, 'n', 'nerd', function()
+    nerd_only = not nerd_only
+    local node = menu.tree:get_node()
+    render(node and node.text)
+  end)
+  ui.map(menu, 'n', 'back', function()
+    close()
+    ui.back()
+  end)
+  ui.map(menu, 'n', 'close', function()
+    close()
+    ui.reset()
+  end)
   -- mount the component
-  menu:mount()
+  ui.mount(menu)
+  if require('nekifoch').config.window.split then
+    pane = require('nekifoch.nui_font_pane')(menu.winid, layout.size.height)
+  end
+  mark()
+  -- Nerd Fonts are tagged by name at once, by their glyphs once fc-list
+  -- answered
+  if not nerd.probed() then
+    nerd.probe_async(function()
+      if not menu.winid then return end
+      if not nerd_only then return mark() end
+      local node = menu.tree:get_node()
+      render(node and node.text)
+    end)
+  end
+  local lnum = find(curFont) or find()
+  if lnum then vim.api.nvim_win_set_cursor(menu.winid, { lnum, 0 }) end
+  local node = menu.tree:get_node()
+  if pane and node and not node.empty then pane.update(node.text) end
+  mounted = true
+  menu.border:set_text('bottom', footer(), 'center')
+end
+
+---@param style NekifochStyle|nil
+---@param fonts string[]|nil Fonts to pick from instead of every font
+---@param on_select fun(font: string)|nil Called instead of applying the font
+return function(style, fonts, on_select)
+  local cache = require('nekifoch.cache')
+  if fonts then return open(fonts, style, false, on_select) end
+  if cache.ready() then
+    return open(select(2, cache.get()), style, true, on_select)
+  end
+
+  local Popup = require('nui.popup')
+  local ui = require('nekifoch.ui')
+  local layout = ui.layout(40, 1)
+  local loading = Popup({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
+    border = {
+      padding = { 0, 1 },
+      style = ui.border('info'),
+      text = {
+        top = ui.title(title(style)),
+        top_align = 'center',
+      },
+    },
+    buf_options = { filetype = 'nekifoch' },
+  })
+  loading:mount()
+  vim.api.nvim_buf_set_lines(loading.bufnr, 0, -1, false, { 'Loading…' })
+  for option, value in pairs(ui.buf_options()) do
+    vim.bo[loading.bufnr][option] = value
+  end
+
+  cache.get_async(function(_, list)
+    loading:unmount()
+    open(list, style, true, on_select)
+  end)
 end
@@ -1,45 +1,115 @@
-return function()
+local func = require('nekifoch.command_func')
+local ui = require('nekifoch.ui')
+
+---@param on_select fun(size: string)|nil Called instead of applying the size
+return function(on_select)
   local Input = require('nui.input')
   local event = require('nui.utils.autocmd').event
 
-  local input = Input({
-    position = { row = '50%', col = '50%' },
-    size = { width = 20 },
+  local config = require('nekifoch').config
+  local ok, current = pcall(require('nekifoch.backend').current().get)
+  local original = ok and current.size or ''
+
+  ---Typed values go through `set_size` as is, so `+2`, `default` and its
+  ---error messages work here too
+  ---@param value string
+  local function apply(value)
+    value = vim.trim(value)
+    if value == '' or value == original then return end
+    require('nekifoch.command').run('set_size', { value })
+  end
+
+  local layout = ui.layout(32)
+  local input
+  input = Input({
+    relative = layout.relative,
+    position = layout.position,
+    size = layout.size,
+    zindex = layout.zindex,
     border = {
-      style = 'rounded',
+      style = ui.border('input'),
       padding = { 0, 1 },
       text = {
-        top = ' Set font size ',
+        top = ui.title(' Set font size '),
         top_align = 'center',
+        bottom = ui.footer({
+          ui.hints({
+            '<CR> apply',
+            -- Letter steps only work in normal mode
+            { 'back', 'steps' },
+            { { 'size_fine_down', 'size_fine_up' }, '0.1' },
+            { { 'size_up', 'size_down' }, tostring(config.size_step) },
+            { { 'size_coarse_up', 'size_coarse_down' }, '2' },
+            { 'close', 'quit' },
+          }),
+          original ~= '' and ('was ' .. original) or nil,
+        }),
       },
     },
-    buf_options = { filetype = 'nekifoch' },
-    win_options = {
-      winhighlight = 'NormalFloat:NormalFloat,FloatBorder:FloatBorder',
-    },
-    relative = 'editor',
+    buf_options = ui.buf_options(true),
+    win_options = ui.win_options(),
   }, {
     prompt = '',
-    default_value = '',
+    default_value = original,
+    on_change = function()
+      if input and input.bufnr then ui.mark_current(input.bufnr, 1) end
+    end,
+    -- Steps only change the value shown, nothing is written before <CR>
     on_submit = function(value)
-      require('nekifoch.utils').replace_font_size(value)
-      if vim.fn.systemlist('pidof kitty')[1] ~= '' then
-        vim.cmd('silent !kill -USR1 $(pidof kitty)')
-      end
+      ui.reset()
+      if on_select then return vim.schedule(function() on_select(value) end) end
+      apply(value)
     end,
   })
+
+  ---@param amount number
+  local function step(amount)
+    local line = vim.api.nvim_buf_get_lines(input.bufnr, 0, 1, false)[1]
+    local size = tonumber(line) or tonumber(original)
+    if not size then return end
+    -- Round away float noise from tenths, e.g. 11.999999
+    size = math.floor((size + amount) * 10 + 0.5) / 10
+    local value = tostring(func.clamp_size(size))
+    vim.api.nvim_buf_set_lines(input.bufnr, 0, 1, false, { value })
+    vim.api.nvim_win_set_cursor(input.winid, { 1, #value })
+    ui.mark_current(input.bufnr, 1)
+  end
+
+  ---Map `action` to steps of `amount`. A count steps several times, typed in
+  ---normal mode since digits in insert mode are part of the size
+  ---@param action string
+  ---@param amount number
+  local function map_step(action, amount)
+    ui.map(input, 'n', action, function() step(vim.v.count1 * amount) end)
+    ui.map(input, 'i', action, function() step(amount) end)
+  end
+
   input:on(event.BufLeave, function() input:unmount() end)
-  input:map(
-    'n',
-    { '<Esc>', 'q', '<C-c>' },
-    function() input:unmount() end,
-    { noremap = true, nowait = true }
-  )
-  input:map(
-    'i',
-    { '<Esc>', '<C-q>', '<C-c>' },
-    function() input:unmount() end,
-    { noremap = true, nowait = true }
-  )
-  input:mount()
+  map_step('size_up', config.size_step)
+  map_step('size_down', -config.size_step)
+  map_step('size_fine_up', 0.1)
+  map_step('size_fine_down', -0.1)
+  map_step('size_coarse_up', 2)
+  map_step('size_coarse_down', -2)
+  ui.map(input, 'n', 'confirm', function()
+    local line = vim.api.nvim_buf_get_lines(input.bufnr, 0, 1, false)[1]
+    input:unmount()
+    ui.reset()
+    if on_select then return vim.schedule(function() on_select(line) end) end
+    apply(line)
+  end)
+  local function back()
+    input:unmount()
+    ui.back()
+  end
+  local function quit()
+    input:unmount()
+    ui.reset()
+  end
+  -- <Esc> leaves insert mode first, where the letter steps and counts work
+  ui.map(input, 'n', 'back', back)
+  ui.map(input, 'n', 'close', quit)
+  ui.map(input, 'i', 'close', quit)
+  ui.mount(input)
+  ui.mark_current(input.bufnr, 1)
 end
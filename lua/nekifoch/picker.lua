@@ -0,0 +1,214 @@
+local M = {}
+
+---@return boolean
+local function ui_select()
+  return require('nekifoch').config.picker == 'ui_select'
+end
+
+---@param cmd string
+---@param args string[]
+local function run(cmd, args) require('nekifoch.command').run(cmd, args) end
+
+---Pick a font family, or the bold/italic font for `style`
+---@param style NekifochStyle|nil
+---@param fonts string[]|nil Fonts to pick from instead of every font
+---@param on_select fun(font: string)|nil Called instead of applying the font
+M.font = function(style, fonts, on_select)
+  if not ui_select() then
+    return require('nekifoch.nui_set_font')(style, fonts, on_select)
+  end
+
+  local prompt = style and ('Set ' .. style:gsub('_', ' ') .. ' font')
+    or 'Set font family'
+  local function select(list)
+    vim.ui.select(list, { prompt = prompt, kind = 'nekifoch' }, function(choice)
+      if not choice then return end
+      if on_select then return on_select(choice) end
+      run(style and ('set_' .. style .. '_font') or 'set_font', { choice })
+    end)
+  end
+  if fonts then return select(fonts) end
+  require('nekifoch.cache').get_async(
+    function(_, list) select(require('nekifoch.favorites').first(list)) end
+  )
+end
+
+---Show `fonts`, applying the one selected
+---@param fonts string[]
+M.list = function(fonts)
+  if not ui_select() then return require('nekifoch.nui_list')(fonts) end
+
+  local ok, current = pcall(require('nekifoch.backend').current().get)
+  vim.ui.select(fonts, {
+    prompt = 'Available fonts',
+    kind = 'nekifoch',
+    format_item = function(font)
+      return (ok and font == current.font and '● ' or '- ') .. font
+    end,
+  }, function(choice)
+    if choice then run('set_font', { choice }) end
+  end)
+end
+
+---@param on_select fun(size: string)|nil Called instead of applying the size
+M.size = function(on_select)
+  if not ui_select() then
+    return require('nekifoch.nui_set_size')(on_select)
+  end
+
+  local ok, current = pcall(require('nekifoch.backend').current().get)
+  vim.ui.input(
+    { prompt = 'Set font size: ', default = ok and current.size or nil },
+    function(value)
+      if not value or value == '' then return end
+      if on_select then return on_select(value) end
+      run('set_size', { value })
+    end
+  )
+end
+
+---Adjust a `modify_font` setting, picking which one first without `what`
+---@param what string|nil
+M.modify = function(what)
+  if not what then
+    return vim.ui.select(
+      require('nekifoch.utils').font_modifications,
+      { prompt = 'Modify font', kind = 'nekifoch' },
+      function(choice)
+        if choice then M.modify(choice) end
+      end
+    )
+  end
+  if not ui_select() then return require('nekifoch.nui_modify')(what) end
+
+  vim.ui.input({
+    prompt = what .. ': ',
+    default = require('nekifoch.utils').get_modify_font(what),
+  }, function(value)
+    if value and value ~= '' then run('modify', { what, value }) end
+  end)
+end
+
+---Toggle OpenType features of the current family
+M.features = function()
+  if not ui_select() then return require('nekifoch.nui_features')() end
+
+  local current = require('nekifoch.backend').current().get()
+  vim.ui.input({
+    prompt = 'Font features: ',
+    default = current.spec and current.spec.features or nil,
+  }, function(value)
+    if value then run('features', { value ~= '' and value or 'none' }) end
+  end)
+end
+
+M.profile = function()
+  if not ui_select() then return require('nekifoch.nui_profile')() end
+
+  local names = vim.tbl_keys(require('nekifoch').config.profiles)
+  table.sort(names)
+  vim.ui.select(
+    names,
+    { prompt = 'Font profile', kind = 'nekifoch' },
+    function(choice)
+      if choice then run('profile', { choice }) end
+    end
+  )
+end
+
+---Subcommands, keymaps and options, in a window or as a message
+M.help = function()
+  local lines = require('nekifoch.help').lines()
+  if ui_select() then return require('nekifoch.notify').info(lines) end
+  require('nekifoch.nui_help')(lines)
+end
+
+---Pick one of `items` and run its action
+---@param items NekifochMenuItem[]
+---@param title string
+M.choose = function(items, title)
+  if not ui_select() then
+    return require('nekifoch.nui_menu')(items, title)
+  end
+
+  vim.ui.select(items, {
+    prompt = title,
+    kind = 'nekifoch',
+    format_item = function(item) return item.label end,
+  }, function(item)
+    if item then item.action() end
+  end)
+end
+
+---@class NekifochMenuItem
+---@field label string
+---@field action fun()
+
+---@param cmd string
+---@return fun()
+local function runs(cmd)
+  return function() run(cmd, {}) end
+end
+
+---Entries of the main menu, then `menu_items` from the config
+---@return NekifochMenuItem[]
+M.menu_items = function()
+  local config = require('nekifoch').config
+  local kitty = require('nekifoch.backend').current().name == 'kitty'
+  local entries = {
+    { label = 'Set font family', action = function() M.font() end },
+    { label = 'Set bold font', action = function() M.font('bold') end },
+    { label = 'Set italic font', action = function() M.font('italic') end },
+    {
+      label = 'Set bold italic font',
+      action = function() M.font('bold_italic') end,
+    },
+    { label = 'Set font size', action = M.size },
+    { label = 'Increase font size', action = runs('size_up') },
+    { label = 'Decrease font size', action = runs('size_down') },
+    {
+      label = 'Switch profile',
+      action = M.profile,
+      shown = not vim.tbl_isempty(config.profiles),
+    },
+    {
+      label = 'Theme and font',
+      action = runs('bundle'),
+      shown = kitty and not vim.tbl_isempty(config.bundles),
+    },
+    { label = 'Favorite fonts', action = runs('favorites') },
+    { label = 'Recent fonts', action = runs('recent') },
+    { label = 'Font features', action = M.features, shown = kitty },
+    { label = 'Check current font', action = runs('check') },
+    { label = 'List fonts', action = runs('list') },
+    { label = 'Install a font', action = runs('install') },
+    { label = 'Help', action = M.help },
+  }
+
+  local items = {}
+  for _, entry in ipairs(entries) do
+    if entry.shown ~= false then
+      table.insert(items, { label = entry.label, action = entry.action })
+    end
+  end
+  return vim.list_extend(items, config.menu_items)
+end
+
+M.menu = function()
+  local items = M.menu_items()
+  if not ui_select() then
+    -- The main menu is where every navigation starts
+    require('nekifoch.ui').reset()
+    return require('nekifoch.nui_menu')(items, nil, M.menu)
+  end
+
+  vim.ui.select(items, {
+    prompt = 'Nekifoch',
+    kind = 'nekifoch',
+    format_item = function(item) return item.label end,
+  }, function(item)
+    if item then item.action() end
+  end)
+end
+
+return M
@@ -0,0 +1,39 @@
+-- <Plug> mappings to bind font actions in any buffer, e.g.
+-- vim.keymap.set('n', '<C-=>', '<Plug>(NekifochSizeUp)')
+local M = {}
+
+---@param cmd string
+---@param args string[]|nil
+local function run(cmd, args)
+  require('nekifoch.command').run(cmd, args or {})
+end
+
+---@param name string
+---@return fun()
+local function picker(name)
+  return function() require('nekifoch.picker')[name]() end
+end
+
+M.mappings = {
+  -- A count steps several times
+  NekifochSizeUp = function() run('size_up', { tostring(vim.v.count1) }) end,
+  NekifochSizeDown = function()
+    run('size_down', { tostring(vim.v.count1) })
+  end,
+  NekifochSizeReset = function() run('set_size', { 'default' }) end,
+  NekifochCheck = function() run('check') end,
+  NekifochNextFont = function() run('next_font') end,
+  NekifochPrevFont = function() run('prev_font') end,
+  NekifochPicker = picker('font'),
+  NekifochSize = picker('size'),
+  NekifochProfile = picker('profile'),
+  NekifochMenu = picker('menu'),
+}
+
+M.setup = function()
+  for name, action in pairs(M.mappings) do
+    vim.keymap.set('n', '<Plug>(' .. name .. ')', action, { desc = name })
+  end
+end
+
+return M
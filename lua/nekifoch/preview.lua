@@ -0,0 +1,146 @@
+local M = {}
+
+---Command line of a kitty window rendering the sample line in `family`
+---@param family string
+---@param size number|nil Font size, kitty.conf's when `nil`
+---@return string[]
+local function kitty_cmd(family, size)
+  local cmd = {
+    'kitty',
+    '--title',
+    family .. (size and (' ' .. size) or ''),
+    '--override',
+    'font_family=' .. family,
+  }
+  if size then vim.list_extend(cmd, { '--override', 'font_size=' .. size }) end
+  return vim.list_extend(cmd, {
+    '--override',
+    'remember_window_size=no',
+    '--override',
+    'initial_window_height=4c',
+    '--hold',
+    'printf',
+    '%s\n',
+    require('nekifoch').config.preview.sample,
+  })
+end
+
+---Open one kitty window per size rendering the sample line in `family`
+---@param family string Font family to preview
+---@param sizes number[] Font sizes to render
+---@return boolean ok
+---@return string|nil err
+M.ladder = function(family, sizes)
+  if vim.fn.executable('kitty') == 0 then
+    return false, 'kitty executable not found'
+  end
+
+  for _, size in ipairs(sizes) do
+    local job = vim.fn.jobstart(kitty_cmd(family, size), { detach = true })
+    if job <= 0 then return false, 'Failed to launch kitty preview' end
+  end
+
+  return true
+end
+
+---@type integer|nil
+local window_job
+
+---Show the sample line in `family` in a kitty window of its own, replacing
+---the one already open. Remote control can't launch a window with another
+---font, so this is a separate kitty process drawn by kitty's own renderer
+---@param family string
+---@return boolean ok
+---@return string|nil err
+M.window = function(family)
+  M.close_window()
+  if vim.fn.executable('kitty') == 0 then
+    return false, 'kitty executable not found'
+  end
+  local job = vim.fn.jobstart(kitty_cmd(family))
+  if job <= 0 then return false, 'Failed to launch kitty preview' end
+  window_job = job
+  return true
+end
+
+---Close the window opened by `window`
+M.close_window = function()
+  if window_job then
+    vim.fn.jobstop(window_job)
+    window_job = nil
+  end
+end
+
+-- Kitty graphics protocol id of the image shown by `show`
+local image_id = 4242
+
+---@return string|nil err Why images can't be shown
+M.images_unavailable = function()
+  if require('nekifoch.detect').terminal() ~= 'kitty' then
+    return 'Rendered previews need kitty'
+  end
+  if vim.env.TMUX then return 'Rendered previews don\'t pass through tmux' end
+  if not vim.base64 then return 'Rendered previews need Neovim 0.10' end
+  if vim.fn.executable('magick') == 0 and vim.fn.executable('convert') == 0 then
+    return 'Rendered previews need ImageMagick'
+  end
+end
+
+---Render the sample line in the font `file`, once per file and sample
+---@param file string Font file
+---@return string|nil png
+M.render = function(file)
+  local sample = require('nekifoch').config.preview.sample
+  local fill = vim.o.background == 'light' and 'black' or 'white'
+  local dir = vim.fn.stdpath('cache') .. '/nekifoch/previews'
+  local png = dir .. '/' .. vim.fn.sha256(file .. fill .. sample) .. '.png'
+  if vim.fn.filereadable(png) == 1 then return png end
+  vim.fn.mkdir(dir, 'p')
+
+  local util = require('nekifoch.utils')
+  util.system({
+    vim.fn.executable('magick') == 1 and 'magick' or 'convert',
+    '-background',
+    'none',
+    '-fill',
+    fill,
+    '-font',
+    file,
+    '-pointsize',
+    '32',
+    'label:' .. sample,
+    png,
+  })
+  if vim.v.shell_error ~= 0 then return nil end
+  return png
+end
+
+---Draw `png` over the text of window `winid` from `row`, `rows` lines high
+---@param png string
+---@param winid integer
+---@param row integer First line of the window covered
+---@param rows integer
+M.show = function(png, winid, row, rows)
+  local pos = vim.fn.screenpos(winid, row, 1)
+  if pos.row == 0 then return end
+  local columns = vim.api.nvim_win_get_width(winid) - 2
+  io.stdout:write(
+    '\27[s'
+      .. string.format('\27[%d;%dH', pos.row, pos.col)
+      .. string.format(
+        '\27_Ga=T,f=100,t=f,q=2,C=1,i=%d,c=%d,r=%d;%s\27\\',
+        image_id,
+        columns,
+        rows,
+        vim.base64.encode(png)
+      )
+      .. '\27[u'
+  )
+end
+
+---Remove the image drawn by `show`
+M.clear = function()
+  io.stdout:write(string.format('\27_Ga=d,d=I,i=%d,q=2\27\\', image_id))
+end
+
+return M
@@ -0,0 +1,29 @@
+local state = require('nekifoch.state')
+
+local M = {}
+
+---@class NekifochRecentFont
+---@field font string
+---@field time integer When it was applied
+
+---Applied fonts, most recent first
+---@return NekifochRecentFont[]
+M.list = function() return state.get('recent') or {} end
+
+---@return string[]
+M.fonts = function()
+  return vim.tbl_map(function(entry) return entry.font end, M.list())
+end
+
+---Record `font` as just applied, keeping `recent.max` fonts
+---@param font string
+M.add = function(font)
+  local recent = { { font = font, time = os.time() } }
+  for _, entry in ipairs(M.list()) do
+    if #recent >= require('nekifoch').config.recent.max then break end
+    if entry.font ~= font then table.insert(recent, entry) end
+  end
+  state.set('recent', recent)
+end
+
+return M
@@ -0,0 +1,100 @@
+-- Fonts switched by the time of day or any condition. A rule only acts
+-- when the set of matching rules changes, so a font picked by hand stays
+-- until the next rule starts or stops matching
+local M = {}
+
+---@class NekifochRule: NekifochProfile
+---@field after string|nil `HH:MM` the rule starts at
+---@field before string|nil `HH:MM` it stops at, can be past midnight
+---@field when fun(): boolean|nil
+
+---@param time string `HH:MM`
+---@return integer|nil minutes Since midnight
+local function minutes(time)
+  local hours, mins = time:match('^(%d%d?):(%d%d)$')
+  if not hours then return nil end
+  return tonumber(hours) * 60 + tonumber(mins)
+end
+
+---@param rule NekifochRule
+---@param now integer Minutes since midnight
+---@return boolean
+local function matches(rule, now)
+  local from = rule.after and minutes(rule.after) or 0
+  local to = rule.before and minutes(rule.before) or 24 * 60
+  local in_time
+  if from <= to then
+    in_time = now >= from and now < to
+  else
+    -- e.g. after 20:00 before 07:00
+    in_time = now >= from or now < to
+  end
+  if not in_time then return false end
+  if rule.when then
+    local ok, result = pcall(rule.when)
+    return ok and result == true
+  end
+  return true
+end
+
+---Indexes of the matching rules, joined
+---@type string|nil
+local last
+
+---Apply the matching rules, later ones winning, when which rules match
+---changed since the last check
+M.evaluate = function()
+  local rules = require('nekifoch').config.rules
+  local date = os.date('*t')
+  local now = date.hour * 60 + date.min
+
+  local matching, profile = {}, {}
+  for i, rule in ipairs(rules) do
+    if matches(rule, now) then
+      table.insert(matching, i)
+      for _, field in ipairs({
+        'family',
+        'size',
+        'bold',
+        'italic',
+        'bold_italic',
+      }) do
+        if rule[field] ~= nil then profile[field] = rule[field] end
+      end
+    end
+  end
+
+  local signature = table.concat(matching, ',')
+  if signature == last then return end
+  last = signature
+  if vim.tbl_isempty(profile) then return end
+
+  require('nekifoch.log').info('rules ' .. signature .. ' apply')
+  -- Run from a timer or autocmd, a confirm diff would pop up mid-typing
+  local _, err = require('nekifoch.command_func').update_profile(profile, true)
+  if err then require('nekifoch.notify').error(err) end
+end
+
+---@type uv_timer_t|nil
+local timer
+
+---Check the rules every minute and when the colorscheme, `background` or
+---focus change
+M.start = function()
+  if timer then return end
+  timer = vim.loop.new_timer()
+  timer:start(0, 60 * 1000, vim.schedule_wrap(M.evaluate))
+
+  local group = vim.api.nvim_create_augroup('NekifochRules', { clear = true })
+  vim.api.nvim_create_autocmd({ 'ColorScheme', 'FocusGained' }, {
+    group = group,
+    callback = function() M.evaluate() end,
+  })
+  vim.api.nvim_create_autocmd('OptionSet', {
+    group = group,
+    pattern = 'background',
+    callback = function() M.evaluate() end,
+  })
+end
+
+return M
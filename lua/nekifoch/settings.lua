@@ -0,0 +1,87 @@
+-- Current font, features, favorites and profiles in one JSON file, to move
+-- them between machines or keep them in dotfiles
+local state = require('nekifoch.state')
+local util = require('nekifoch.utils')
+
+local M = {}
+
+---@class NekifochSettings
+---@field font NekifochProfile
+---@field features table<string, string> Per family
+---@field favorites string[]
+---@field profiles table<string, NekifochProfile>
+
+---@return NekifochSettings
+M.collect = function()
+  local current = require('nekifoch.backend').current().get()
+  local font = { family = current.font, size = tonumber(current.size) }
+  for _, style in ipairs(util.styles) do
+    font[style] = current[style]
+  end
+  return {
+    font = font,
+    features = state.get('features') or {},
+    favorites = require('nekifoch.favorites').list(),
+    profiles = require('nekifoch').config.profiles,
+  }
+end
+
+---@param path string
+---@return string|nil err
+M.export = function(path)
+  local ok, json = pcall(vim.json.encode, M.collect())
+  if not ok then return json end
+  vim.fn.mkdir(vim.fn.fnamemodify(path, ':h'), 'p')
+  if vim.fn.writefile({ json }, path) ~= 0 then
+    return 'Could not write ' .. path
+  end
+end
+
+---Merge the features, favorites and profiles of `path` into the saved
+---ones, then apply its font where it differs from the config
+---@param path string
+---@return NekifochFont|nil change
+---@return string|nil err
+M.import = function(path)
+  if vim.fn.filereadable(path) == 0 then return nil, 'No file ' .. path end
+  local ok, settings = pcall(vim.json.decode, util.read_file(path))
+  if not ok or type(settings) ~= 'table' then
+    return nil, path .. ' is not a settings file'
+  end
+
+  if type(settings.features) == 'table' then
+    state.set(
+      'features',
+      vim.tbl_extend('force', state.get('features') or {}, settings.features)
+    )
+  end
+  if type(settings.favorites) == 'table' then
+    local favorites = require('nekifoch.favorites').list()
+    for _, font in ipairs(settings.favorites) do
+      if not vim.tbl_contains(favorites, font) then
+        table.insert(favorites, font)
+      end
+    end
+    table.sort(favorites)
+    state.set('favorites', favorites)
+  end
+  if type(settings.profiles) == 'table' then
+    state.set(
+      'profiles',
+      vim.tbl_extend('force', state.get('profiles') or {}, settings.profiles)
+    )
+    M.load_profiles()
+  end
+
+  if type(settings.font) ~= 'table' then return nil end
+  return require('nekifoch.command_func').update_profile(settings.font)
+end
+
+---Add imported profiles to `profiles`, winning over the configured ones
+M.load_profiles = function()
+  local config = require('nekifoch').config
+  config.profiles =
+    vim.tbl_extend('force', config.profiles, state.get('profiles') or {})
+end
+
+return M
@@ -0,0 +1,32 @@
+-- Small JSON state kept between sessions, e.g. favorite fonts
+local M = {}
+
+---@return string
+M.file = function() return vim.fn.stdpath('data') .. '/nekifoch/state.json' end
+
+---@type table|nil
+local state = nil
+
+---@return table
+M.load = function()
+  if state then return state end
+  local ok, saved = pcall(function()
+    return vim.json.decode(require('nekifoch.utils').read_file(M.file()))
+  end)
+  state = ok and type(saved) == 'table' and saved or {}
+  return state
+end
+
+---@param key string
+---@return any
+M.get = function(key) return M.load()[key] end
+
+---@param key string
+---@param value any
+M.set = function(key, value)
+  M.load()[key] = value
+  vim.fn.mkdir(vim.fn.fnamemodify(M.file(), ':h'), 'p')
+  vim.fn.writefile({ vim.json.encode(state) }, M.file())
+end
+
+return M
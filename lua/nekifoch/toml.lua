@@ -0,0 +1,93 @@
+-- Line based editing of TOML configs that keeps comments and formatting,
+-- shared by the Alacritty and Rio backends
+local M = {}
+
+---@param line string
+---@return string|nil
+M.header = function(line)
+  local name = line:match('^%s*%[([^%[%]]+)%]%s*$')
+  return name and vim.trim(name)
+end
+
+-- Places a key can be written in: table header, line pattern and the key
+-- whose value is replaced on that line
+---@alias NekifochTomlKey { [1]: string, [2]: string, [3]: string }
+
+---First line matching one of `keys`, tried in order
+---@param lines string[]
+---@param keys NekifochTomlKey[]
+---@return integer|nil index
+---@return string|nil key
+M.locate = function(lines, keys)
+  for _, candidate in ipairs(keys) do
+    local section = ''
+    for i, line in ipairs(lines) do
+      section = M.header(line) or section
+      if section == candidate[1] and line:match(candidate[2]) then
+        return i, candidate[3]
+      end
+    end
+  end
+end
+
+---Replace the value of `key` on `line`, keeping comments and formatting
+---@param line string
+---@param key string Lua pattern of the key
+---@param value string TOML value
+---@return string
+M.splice = function(line, key, value)
+  local _, eq = line:find(key .. '%s*=%s*')
+  local rest = line:sub(eq + 1)
+  local token = rest:match('^"[^"]*"')
+    or rest:match("^'[^']*'")
+    or rest:match('^[%w%.%-%+]+')
+    or ''
+  return line:sub(1, eq) .. value .. rest:sub(#token + 1)
+end
+
+---String value of `key` on `line`
+---@param line string
+---@param key string Lua pattern of the key
+---@return string|nil
+M.string = function(line, key)
+  return line:match(key .. '%s*=%s*"([^"]*)"')
+    or line:match(key .. "%s*=%s*'([^']*)'")
+end
+
+---Set `key` in `lines`, adding the `section` table when it is missing
+---@param lines string[]
+---@param keys NekifochTomlKey[]
+---@param section string
+---@param entry string `key = value` line for a new entry
+---@param value string
+---@return NekifochEditAction
+M.set_value = function(lines, keys, section, entry, value)
+  local index, key = M.locate(lines, keys)
+  if index then
+    lines[index] = M.splice(lines[index], key, value)
+    return 'replaced'
+  end
+
+  for i, line in ipairs(lines) do
+    if M.header(line) == section then
+      table.insert(lines, i + 1, entry)
+      return 'appended'
+    end
+  end
+
+  -- Keep the trailing newline at the end of the file
+  local at = lines[#lines] == '' and #lines or #lines + 1
+  for offset, line in ipairs({ '', '[' .. section .. ']', entry }) do
+    table.insert(lines, at + offset - 1, line)
+  end
+  return 'appended'
+end
+
+---@param size number
+---@return string
+M.format_size = function(size)
+  if math.floor(size) == size then return string.format('%.1f', size) end
+  return tostring(size)
+end
+
+return M
@@ -0,0 +1,313 @@
+-- Helpers shared by the nui windows
+local M = {}
+
+-- Links colorschemes and users can override
+M.highlights = {
+  NekifochTitle = 'FloatTitle',
+  NekifochBorder = 'FloatBorder',
+  NekifochCurrentFont = 'Special',
+  NekifochSelected = 'PmenuSel',
+  NekifochHint = 'Comment',
+  NekifochFavorite = 'DiagnosticWarn',
+  NekifochNerd = 'DiagnosticInfo',
+  -- `syntax/nekifoch.lua`
+  NekifochLabel = 'Label',
+  NekifochNumber = 'Number',
+  NekifochSeparator = 'NonText',
+}
+
+M.winhighlight = table.concat({
+  'NormalFloat:NormalFloat',
+  'FloatBorder:NekifochBorder',
+  'CursorLine:NekifochSelected',
+}, ',')
+
+---Define the highlight groups without overriding existing ones, again after
+---a colorscheme clears them
+M.setup_highlights = function()
+  local function define()
+    for group, link in pairs(M.highlights) do
+      vim.api.nvim_set_hl(0, group, { link = link, default = true })
+    end
+  end
+  define()
+  vim.api.nvim_create_autocmd('ColorScheme', {
+    group = vim.api.nvim_create_augroup('NekifochHighlights', {}),
+    callback = define,
+  })
+end
+
+local namespace = vim.api.nvim_create_namespace('nekifoch')
+
+---Mark the line showing the active font, replacing an earlier mark
+---@param bufnr integer
+---@param lnum integer|nil 1-based line, `nil` only clears the mark
+M.mark_current = function(bufnr, lnum)
+  vim.api.nvim_buf_clear_namespace(bufnr, namespace, 0, -1)
+  if not lnum then return end
+  local line = vim.api.nvim_buf_get_lines(bufnr, lnum - 1, lnum, false)[1]
+  vim.api.nvim_buf_set_extmark(bufnr, namespace, lnum - 1, 0, {
+    end_col = #line,
+    hl_group = 'NekifochCurrentFont',
+    virt_text = { { '●', 'NekifochCurrentFont' } },
+    virt_text_pos = 'eol',
+  })
+end
+
+local favorites_namespace = vim.api.nvim_create_namespace('nekifoch_favorites')
+
+---Star favorite fonts
+---@param bufnr integer
+---@param lnums integer[] 1-based lines
+M.mark_favorites = function(bufnr, lnums)
+  vim.api.nvim_buf_clear_namespace(bufnr, favorites_namespace, 0, -1)
+  for _, lnum in ipairs(lnums) do
+    vim.api.nvim_buf_set_extmark(bufnr, favorites_namespace, lnum - 1, 0, {
+      virt_text = { { '★', 'NekifochFavorite' } },
+      virt_text_pos = 'eol',
+    })
+  end
+end
+
+local nerd_namespace = vim.api.nvim_create_namespace('nekifoch_nerd')
+
+---Tag Nerd Fonts
+---@param bufnr integer
+---@param lnums integer[] 1-based lines
+M.mark_nerd = function(bufnr, lnums)
+  vim.api.nvim_buf_clear_namespace(bufnr, nerd_namespace, 0, -1)
+  for _, lnum in ipairs(lnums) do
+    vim.api.nvim_buf_set_extmark(bufnr, nerd_namespace, lnum - 1, 0, {
+      virt_text = { { 'NF', 'NekifochNerd' } },
+      virt_text_pos = 'eol',
+    })
+  end
+end
+
+---Window title
+---@param text string
+---@return NuiText
+M.title = function(text) return require('nui.text')(text, 'NekifochTitle') end
+
+---Height of a list window for `count` lines, capped by `window.max_height`
+---and the editor size
+---@param count integer
+---@return integer
+M.height = function(count)
+  local max = require('nekifoch').config.window.max_height
+  return math.max(1, math.min(count, max, vim.o.lines - 6))
+end
+
+---@class NekifochLayout
+---@field relative string
+---@field position { row: number|string, col: number|string }
+---@field size { width: integer, height: integer|nil }
+---@field zindex integer|nil
+
+---Geometry of a window from `window` settings, falling back to `width` and a
+---height fitting `count` lines
+---@param width integer
+---@param count integer|nil Lines of a list, `nil` for one line inputs
+---@return NekifochLayout
+M.layout = function(width, count)
+  local opts = require('nekifoch').config.window
+  width = opts.width or width
+  local relative, row, col = 'editor', '50%', '50%'
+  if opts.anchor == 'cursor' then
+    relative, row, col = 'cursor', 1, 0
+  elseif opts.anchor == 'top_right' then
+    row, col = 1, math.max(0, vim.o.columns - width - 4)
+  end
+
+  return {
+    relative = relative,
+    position = { row = opts.row or row, col = opts.col or col },
+    size = {
+      width = width,
+      height = count and (opts.height or M.height(count)),
+    },
+    zindex = opts.zindex,
+  }
+end
+
+---@alias NekifochWindowKind "'menu'"|"'picker'"|"'input'"|"'info'"
+
+---Border style for a kind of window. `borders` is a style name, eight
+---border characters like `nvim_open_win()` takes, or a table of those per
+---window kind with a `default`
+---@param kind NekifochWindowKind
+---@return string|string[]
+M.border = function(kind)
+  local borders = require('nekifoch').config.borders
+  if type(borders) ~= 'table' or borders[1] then return borders end
+  return borders[kind] or borders.default or 'rounded'
+end
+
+---Scratch buffer options, list windows can't be edited. nui inputs keep
+---their prompt buffer modifiable
+---@param input boolean|nil
+---@return table
+M.buf_options = function(input)
+  local options = {
+    filetype = 'nekifoch',
+    bufhidden = 'wipe',
+    swapfile = false,
+  }
+  if not input then
+    options.buftype = 'nofile'
+    options.modifiable = false
+  end
+  return options
+end
+
+---@return table
+M.win_options = function()
+  return {
+    winhighlight = M.winhighlight,
+    winblend = require('nekifoch').config.window.winblend,
+  }
+end
+
+---Scroll position of a list window, e.g. `3/120`, empty when every line fits
+---@param winid integer
+---@return string
+M.position = function(winid)
+  local total = vim.api.nvim_buf_line_count(vim.api.nvim_win_get_buf(winid))
+  if total <= vim.api.nvim_win_get_height(winid) then return '' end
+  return vim.api.nvim_win_get_cursor(winid)[1] .. '/' .. total
+end
+
+---Keys mapped to a window action in `keymaps`
+---@param action string
+---@return string[]
+M.keys = function(action)
+  local keys = require('nekifoch').config.keymaps[action]
+  if type(keys) == 'string' then return { keys } end
+  return keys or {}
+end
+
+---Map the keys of `action`. Insert mode only gets keys that don't type text
+---@param component NuiPopup
+---@param mode string
+---@param action string
+---@param handler fun()
+M.map = function(component, mode, action, handler)
+  local keys = M.keys(action)
+  if mode == 'i' then
+    keys = vim.tbl_filter(function(key) return key:sub(1, 1) == '<' end, keys)
+  end
+  if #keys > 0 then
+    component:map(mode, keys, handler, { noremap = true, nowait = true })
+  end
+end
+
+---Keymaps of a list window as nui menu expects them, closing is mapped by
+---the window itself
+---@return table
+M.menu_keymap = function()
+  return {
+    focus_next = M.keys('next'),
+    focus_prev = M.keys('prev'),
+    close = {},
+    submit = M.keys('confirm'),
+  }
+end
+
+---Keymap hints for a window footer, `nil` when `window.hints` is off.
+---A hint is literal text or `{ actions, label }` showing the first key of
+---each action
+---@param hints (string|table)[]
+---@return string|nil
+M.hints = function(hints)
+  if not require('nekifoch').config.window.hints then return nil end
+  local text = {}
+  for _, hint in ipairs(hints) do
+    if type(hint) == 'string' then
+      table.insert(text, hint)
+    else
+      local actions = type(hint[1]) == 'table' and hint[1] or { hint[1] }
+      local keys = {}
+      for _, action in ipairs(actions) do
+        table.insert(keys, M.keys(action)[1])
+      end
+      if #keys > 0 then
+        table.insert(text, table.concat(keys, '/') .. ' ' .. hint[2])
+      end
+    end
+  end
+  if #text == 0 then return nil end
+  return table.concat(text, ' · ')
+end
+
+---Join footer parts, skipping empty ones
+---@param parts (string|nil)[]
+---@return NuiText
+M.footer = function(parts)
+  local text = {}
+  for i = 1, table.maxn(parts) do
+    if parts[i] and parts[i] ~= '' then table.insert(text, parts[i]) end
+  end
+  local NuiText = require('nui.text')
+  if #text == 0 then return NuiText('') end
+  return NuiText(' ' .. table.concat(text, ' · ') .. ' ', 'NekifochHint')
+end
+
+---@type NuiPopup|nil Window shown now
+M.active = nil
+
+---Views to return to with <Esc>, latest last
+---@type fun()[]
+M.stack = {}
+
+local group
+
+---Keep the window shown centered when the editor is resized, and close it
+---when its tabpage is left, where it would be orphaned
+local function follow_editor()
+  if group then return end
+  group = vim.api.nvim_create_augroup('NekifochWindows', { clear = true })
+  vim.api.nvim_create_autocmd('VimResized', {
+    group = group,
+    callback = function()
+      local active = M.active
+      if not (active and active.winid) then return end
+      -- Percentages of `window` settings resolve against the new size
+      pcall(active.update_layout, active)
+    end,
+  })
+  vim.api.nvim_create_autocmd('TabLeave', {
+    group = group,
+    callback = function()
+      local active = M.active
+      if active and active.winid then active:unmount() end
+      M.active = nil
+      M.reset()
+    end,
+  })
+end
+
+---Show `component` in place of the window already open
+---@param component NuiPopup
+M.mount = function(component)
+  follow_editor()
+  if M.active and M.active ~= component and M.active.winid then
+    M.active:unmount()
+  end
+  M.active = component
+  component:mount()
+end
+
+---Remember how to reopen the current view before moving on to another
+---@param reopen fun()
+M.push = function(reopen) table.insert(M.stack, reopen) end
+
+---Reopen the previous view, if any
+M.back = function()
+  local reopen = table.remove(M.stack)
+  if reopen then vim.schedule(reopen) end
+end
+
+---Forget previous views once a view is done with
+M.reset = function() M.stack = {} end
+
+return M
@@ -1,37 +1,409 @@
 local M = {}
 
----@return table
-M.get = function()
-  local f = assert(io.open(require('nekifoch').config.kitty_conf_path, 'r'))
+-- Errors raised with level 0 carry no code position, `command.execute`
+-- reports them to the user as is
+
+---Raise an error when an external program is missing
+---@param name string
+---@param hint string What to install
+M.require_executable = function(name, hint)
+  if vim.fn.executable(name) == 0 then
+    error(name .. ' not found, install ' .. hint, 0)
+  end
+end
+
+---Content of files edited inside `M.batch`, by resolved path
+---@type table<string, string>|nil
+local pending = nil
+
+---Absolute path with symlinks resolved, also chains of them. Configs are
+---often linked into a dotfiles repository, writing to the target keeps the
+---link in place
+---@param path string
+---@return string
+M.resolve = function(path)
+  return vim.fn.resolve(vim.fn.fnamemodify(path, ':p'))
+end
+
+---@param path string
+---@return string
+M.read_file = function(path)
+  local edited = pending and pending[M.resolve(path)]
+  if edited then return edited end
+  local f, err = io.open(path, 'r')
+  if not f then error('Cannot read config: ' .. err, 0) end
   local content = f:read('*all')
   f:close()
+  return content
+end
+
+---Write `content` to `path`, backing up the previous version first
+---@param path string
+---@param content string
+---@param no_backup boolean|nil Putting back a version, which isn't backed up
+M.write_file = function(path, content, no_backup)
+  path = M.resolve(path)
+  if pending then
+    pending[path] = content
+    return
+  end
+  M.check_writable(path)
+  if not no_backup then require('nekifoch.backup').create(path) end
+  local f, err = io.open(path, 'w')
+  if not f then
+    require('nekifoch.log').error('cannot write ' .. path .. ': ' .. err)
+    error('Cannot write config: ' .. err, 0)
+  end
+  f:write(content)
+  f:close()
+  M.invalidate()
+  require('nekifoch.log').info('wrote ' .. path)
+end
 
-  local current_font_family, current_font_size
-  for line in content:gmatch('[^\n]+') do
-    if line:match('^font_family%s+[^%d]+') then
-      current_font_family = line:match('^font_family%s+(.+)$')
-    elseif line:match('^font_size%s+%d+') then
-      current_font_size = line:match('^font_size%s+(%d+)')
+---Raise an explanation instead of a bare IO error when `path` can't be
+---written, e.g. a config generated into the Nix store
+---@param path string Resolved path
+M.check_writable = function(path)
+  local exists = vim.loop.fs_stat(path) ~= nil
+  local target = exists and path or vim.fn.fnamemodify(path, ':h')
+  if vim.loop.fs_access(target, 'W') then return end
+
+  local reason = vim.startswith(path, '/nix/store/')
+      and ' is in the Nix store, it is generated by Nix or home-manager.'
+    or ' is read-only, it is probably generated or managed by another tool.'
+  local hint
+  if not exists then
+    hint = ' Make its directory writable.'
+  elseif path == M.resolve(M.managed_path()) then
+    hint = ' Make it writable.'
+  else
+    -- The include can't be added to a read-only file, only where it is made
+    hint = M.include_hint()
+  end
+  hint = hint
+    .. ' With confirm set, the diff shows the lines to change where the'
+    .. ' config is generated.'
+  require('nekifoch.log').error(path .. ' is not writable')
+  error(path .. reason .. hint, 0)
+end
+
+---Run `fn` keeping its edits in memory, then write every changed file once.
+---Nothing is written when `fn` fails
+---@param fn fun()
+---@return table<string, string|false> previous Content of the written files
+---before, `false` for new ones
+M.batch = function(fn)
+  if pending then
+    fn()
+    return {}
+  end
+  -- Backups of all files of the change share a stamp, `restore` undoes them
+  -- together
+  return require('nekifoch.backup').group(function()
+    pending = {}
+    local ok, err = pcall(fn)
+    local files = pending
+    pending = nil
+    if not ok then error(err, 0) end
+
+    -- Fail before writing anything
+    for path in pairs(files) do
+      M.check_writable(path)
+    end
+    local previous = {}
+    for path, content in pairs(files) do
+      local readable, old = pcall(M.read_file, path)
+      previous[path] = readable and old
+      M.write_file(path, content)
+    end
+    return previous
+  end)
+end
+
+---Put back files as `batch` found them. The rejected version isn't backed
+---up, `restore` would bring it back
+---@param previous table<string, string|false>
+M.restore_files = function(previous)
+  for path, content in pairs(previous) do
+    if content then
+      M.write_file(path, content, true)
+    else
+      os.remove(path)
     end
   end
+end
 
-  return { font = current_font_family, size = current_font_size }
+---Set while `dry_run` runs, for changes made without writing files
+M.dry = false
+
+---Files `fn` would change and their new content, by absolute path, without
+---writing anything
+---@param fn fun()
+---@return table<string, string>
+M.dry_run = function(fn)
+  local previous, was_dry = pending, M.dry
+  pending, M.dry = {}, true
+  local ok, err = pcall(fn)
+  local files = pending
+  pending, M.dry = previous, was_dry
+  if not ok then error(err, 0) end
+  return files
+end
+
+---Run `cmd` like `vim.fn.system()`, logging it and its failures.
+---`vim.v.shell_error` is left as the command set it
+---@param cmd string[] Program and arguments, run without a shell
+---@return string output
+M.system = function(cmd)
+  local log = require('nekifoch.log')
+  log.debug('run: ' .. log.format_cmd(cmd))
+  local output = vim.fn.system(cmd)
+  if vim.v.shell_error ~= 0 then
+    log.warn(
+      log.format_cmd(cmd)
+        .. ' exited with '
+        .. vim.v.shell_error
+        .. ': '
+        .. vim.trim(output)
+    )
+  end
+  return output
 end
 
-M.listInstalledFonts = function()
-  local cmd = "fc-list : family 2>/dev/null | awk -F ',' '{print $1}'"
-  local handle = io.popen(cmd)
-  if not handle then
-    return {} -- Return an empty list if the command couldn't be executed
+---Run `cmds` side by side and wait for all of them
+---@param cmds string[][]
+---@return string[] outputs Stdout of each command
+---@return integer[] codes Exit codes, negative when one didn't start
+M.system_parallel = function(cmds)
+  local log = require('nekifoch.log')
+  local outputs, jobs = {}, {}
+  for i, cmd in ipairs(cmds) do
+    outputs[i] = ''
+    log.debug('run: ' .. log.format_cmd(cmd))
+    jobs[i] = vim.fn.jobstart(cmd, {
+      stdout_buffered = true,
+      on_stdout = function(_, data) outputs[i] = table.concat(data, '\n') end,
+    })
   end
-  local result = handle:read('*a')
-  handle:close()
+  local codes = vim.fn.jobwait(jobs)
+  for i, code in ipairs(codes) do
+    if code ~= 0 then
+      log.warn(log.format_cmd(cmds[i]) .. ' exited with ' .. code)
+    end
+  end
+  return outputs, codes
+end
+
+---`M.system()` returning lines
+---@param cmd string[]
+---@return string[]
+M.systemlist = function(cmd)
+  return vim.split(M.system(cmd), '\n', { trimempty = true })
+end
+
+---Delay `fn` until it stops being called for `ms` milliseconds
+---@param ms integer
+---@param fn function
+---@return fun(...) call
+---@return fun() cancel
+M.debounce = function(ms, fn)
+  local timer = vim.loop.new_timer()
+  local function call(...)
+    local args = { ... }
+    timer:stop()
+    timer:start(ms, 0, vim.schedule_wrap(function() fn(unpack(args)) end))
+  end
+  return call, function() timer:stop() end
+end
+
+-- Kitty `<style>_font` options next to `font_family`
+M.styles = { 'bold', 'italic', 'bold_italic' }
 
+---@param path string
+---@return string
+local function mtime(path)
+  local stat = vim.loop.fs_stat(path)
+  return stat and (stat.mtime.sec .. '.' .. stat.mtime.nsec) or ''
+end
+
+---Result of the last `M.get()`, kept until one of the files it was read from
+---changes
+---@type { path: string, font: table, mtimes: table<string, string> }|nil
+local current = nil
+
+---Forget the cached font settings
+M.invalidate = function() current = nil end
+
+---@return boolean
+local function current_fresh()
+  local path = require('nekifoch').config.kitty_conf_path
+  if not current or current.path ~= path or pending then return false end
+  for file, time in pairs(current.mtimes) do
+    if mtime(file) ~= time then return false end
+  end
+  return true
+end
+
+---Font settings in effect, following `include` directives. The parsed
+---config is reused while its files are unchanged
+---@return table
+M.get = function()
+  if current_fresh() then return vim.deepcopy(current.font) end
+
+  local kitty_conf = require('nekifoch.kitty_conf')
+  local path = require('nekifoch').config.kitty_conf_path
+  local settings, files = kitty_conf.effective(path)
+  local function value(option)
+    return settings[option] and settings[option].value or nil
+  end
+  -- Only the family of the extended `family="..." style="..."` form
+  local function family(option)
+    if not value(option) then return nil end
+    local spec = kitty_conf.parse_font(value(option))
+    return kitty_conf.font_field(spec, 'family')
+  end
+
+  -- Extended fields besides the family, e.g. `style` and `features`
+  local spec
+  local font_family = kitty_conf.parse_font(value('font_family') or '')
+  for _, field in ipairs(font_family.fields) do
+    if field[1] ~= 'family' then
+      spec = spec or {}
+      spec[field[1]] = field[2]
+    end
+  end
+
+  local current_font_family = family('font_family')
+  local current_font_size = (value('font_size') or ''):match('^[%d%.]+')
+
+  local font = {
+    font = current_font_family,
+    size = current_font_size,
+    bold = family('bold_font'),
+    italic = family('italic_font'),
+    bold_italic = family('bold_italic_font'),
+    spec = spec,
+  }
+
+  -- New files in the config directory may be picked up by `globinclude`
+  local mtimes = { [vim.fn.fnamemodify(path, ':h')] = '' }
+  for _, file in ipairs(files) do
+    mtimes[file] = ''
+  end
+  for file in pairs(mtimes) do
+    mtimes[file] = mtime(file)
+  end
+  if not pending then
+    current = { path = path, font = font, mtimes = mtimes }
+  end
+  return vim.deepcopy(font)
+end
+
+-- Run without a shell, the names are cut at the first comma when parsed
+local installed_fonts_cmd = { 'fc-list', ':', 'family' }
+-- `:spacing=mono` would drop dual width fonts (90), filtered when parsed
+local monospace_fonts_cmd =
+  { 'fc-list', '--format', '%{family[0]}\t%{spacing}\n' }
+-- Windows has no fontconfig, ask .NET for the installed families instead
+local windows_fonts_cmd = {
+  'powershell',
+  '-NoProfile',
+  '-Command',
+  'Add-Type -AssemblyName System.Drawing; '
+    .. '(New-Object System.Drawing.Text.InstalledFontCollection).Families'
+    .. ' | ForEach-Object { $_.Name }',
+}
+
+-- macOS may come without fontconfig, CoreText fonts are listed there
+local mac_fonts_cmd = { 'system_profiler', 'SPFontsDataType' }
+
+---@return boolean
+M.is_windows = function() return vim.fn.has('win32') == 1 end
+
+---@return boolean
+M.is_mac = function() return vim.fn.has('mac') == 1 end
+
+-- Families are printed unindented, their faces indented below them
+local kitty_fonts_cmd = { 'kitty', '+list-fonts', '--psnames' }
+-- kitty's Python API, only for builds where the CLI lists nothing
+local kitty_runpy_cmd = {
+  'kitty',
+  '+runpy',
+  'from kitty.fonts.list import create_family_groups; '
+    .. 'print("\\n".join(create_family_groups()))',
+}
+
+---@param result string Output of kitty +list-fonts
+---@return boolean
+local function has_families(result)
+  return result:find('^%S') ~= nil or result:find('\n%S') ~= nil
+end
+
+---Installed kitty version, e.g. `{ 0, 35, 2 }`
+---@return integer[]|nil
+M.kitty_version = function()
+  if vim.fn.executable('kitty') == 0 then return nil end
+  local major, minor, patch =
+    M.system({ 'kitty', '--version' }):match('(%d+)%.(%d+)%.(%d+)')
+  if not major then return nil end
+  return { tonumber(major), tonumber(minor), tonumber(patch) }
+end
+
+---Run a command in the background and pass its stdout to `callback`
+---@param cmd string[] Program and arguments, run without a shell
+---@param callback fun(result: string)
+M.read_async = function(cmd, callback)
+  local log = require('nekifoch.log')
+  log.debug('start: ' .. log.format_cmd(cmd))
+  local output = {}
+  local job = vim.fn.jobstart(cmd, {
+    stdout_buffered = true,
+    on_stdout = function(_, data) output = data end,
+    on_exit = function(_, code)
+      if code ~= 0 then
+        log.warn(log.format_cmd(cmd) .. ' exited with ' .. code)
+      end
+      callback(table.concat(output, '\n'))
+    end,
+  })
+  if job <= 0 then
+    log.error('could not start: ' .. log.format_cmd(cmd))
+    callback('')
+  end
+end
+
+---Run a command in the background, `callback` gets whether it succeeded
+---@param cmd string[]
+---@param callback fun(ok: boolean)
+M.run_async = function(cmd, callback)
+  local log = require('nekifoch.log')
+  log.debug('start: ' .. log.format_cmd(cmd))
+  local job = vim.fn.jobstart(cmd, {
+    on_exit = function(_, code)
+      if code ~= 0 then
+        log.warn(log.format_cmd(cmd) .. ' exited with ' .. code)
+      end
+      callback(code == 0)
+    end,
+  })
+  if job <= 0 then
+    log.error('could not start: ' .. log.format_cmd(cmd))
+    callback(false)
+  end
+end
+
+---@param result string Output of fc-list, a family and its localized names
+---separated by commas per line
+---@return string[]
+M.parseInstalledFonts = function(result)
   local installedFonts = {}
   local hash = {}
 
-  for font in result:gmatch('[^\r\n]+') do
-    if font then
+  for line in result:gmatch('[^\r\n]+') do
+    local font = vim.trim(line:match('^[^,]*'))
+    -- `system()` mixes stderr in, no longer sent to /dev/null by a shell
+    local warning = font:match('^Fontconfig %a+:')
+    if font ~= '' and not warning then
       if not hash[font] then
         installedFonts[#installedFonts + 1] = font
         hash[font] = true
@@ -42,6 +414,82 @@ M.listInstalledFonts = function()
   return installedFonts
 end
 
+---@param result string Output of fc-list, a family and its spacing per line
+---@return string[]
+M.parseMonospaceFonts = function(result)
+  local installedFonts = {}
+  local hash = {}
+
+  for line in result:gmatch('[^\r\n]+') do
+    local font, spacing = line:match('^([^\t]*)\t(%d*)$')
+    -- Mono (100) and dual width (90) fonts, as font_info tells them
+    if font and tonumber(spacing) and tonumber(spacing) >= 90 then
+      font = vim.trim(font)
+      if font ~= '' and not hash[font] then
+        installedFonts[#installedFonts + 1] = font
+        hash[font] = true
+      end
+    end
+  end
+
+  return installedFonts
+end
+
+---@param result string Output of system_profiler SPFontsDataType
+---@return string[]
+M.parseMacFonts = function(result)
+  local installedFonts = {}
+  local hash = {}
+
+  for font in result:gmatch('Family: ([^\r\n]+)') do
+    if not hash[font] then
+      installedFonts[#installedFonts + 1] = font
+      hash[font] = true
+    end
+  end
+
+  table.sort(installedFonts)
+  return installedFonts
+end
+
+---Command listing installed families on this platform and its parser
+---@param monospace boolean|nil Only monospace families, where fontconfig
+---can tell
+---@return string[] cmd
+---@return fun(result: string): string[] parse
+---@return string tool Executable the command needs
+---@return string hint What to install when `tool` is missing
+local function installed_fonts_source(monospace)
+  if M.is_windows() then
+    return windows_fonts_cmd,
+      M.parseInstalledFonts,
+      'powershell',
+      'Windows PowerShell'
+  end
+  if M.is_mac() and vim.fn.executable('fc-list') == 0 then
+    return mac_fonts_cmd, M.parseMacFonts, 'system_profiler', 'macOS'
+  end
+  if monospace then
+    return monospace_fonts_cmd, M.parseMonospaceFonts, 'fc-list', 'fontconfig'
+  end
+  return installed_fonts_cmd, M.parseInstalledFonts, 'fc-list', 'fontconfig'
+end
+
+---@param monospace boolean|nil
+---@return string[]
+M.listInstalledFonts = function(monospace)
+  local cmd, parse, tool, hint = installed_fonts_source(monospace)
+  M.require_executable(tool, hint)
+  return parse(M.system(cmd))
+end
+
+---@param callback fun(installedFonts: string[])
+---@param monospace boolean|nil
+M.listInstalledFontsAsync = function(callback, monospace)
+  local cmd, parse = installed_fonts_source(monospace)
+  M.read_async(cmd, function(result) callback(parse(result)) end)
+end
+
 -- M.compareFontsWithKittyListFonts = function(installedFonts)
 --   local handle = io.popen('kitty +list-fonts 2>/dev/null') -- Redirect stderr to /dev/null
 --   if not handle then
@@ -74,12 +522,25 @@ end
 --   return compatibleFonts
 -- end
 
-M.compareFontsWithKittyListFonts = function(installedFonts)
-  local handle = io.popen('kitty +list-fonts 2>/dev/null') -- Redirect stderr to /dev/null
-  if not handle then return {}, {} end
-  local result = handle:read('*a')
-  handle:close()
+---Key every font by its name without spaces, for terminals without a filter
+---@param installedFonts string[]
+---@return table<string, string> formattedFonts
+---@return string[] fonts
+M.formatFonts = function(installedFonts)
+  local formattedFonts = {}
+  for _, font in ipairs(installedFonts) do
+    formattedFonts[(font:gsub('%s+', ''))] = font
+  end
+  local fonts = vim.list_extend({}, installedFonts)
+  table.sort(fonts)
+  return formattedFonts, fonts
+end
 
+---@param installedFonts string[]
+---@param result string Output of kitty +list-fonts
+---@return table<string, string> compatibleFormattedFonts
+---@return string[] compatibleFonts
+M.parseKittyFonts = function(installedFonts, result)
   local kittyFonts = {}
   local formattedFontsMap = {}
 
@@ -103,44 +564,446 @@ M.compareFontsWithKittyListFonts = function(installedFonts)
   return compatibleFormattedFonts, compatibleFonts
 end
 
-M.replace_font_family = function(...)
-  local new_font_family = table.concat({ ... }, ' ')
-  local f = assert(io.open(require('nekifoch').config.kitty_conf_path, 'r'))
-  local content = f:read('*all')
-  f:close()
+---Installed fonts kitty can use. fc-list and kitty are both slow to start,
+---they run side by side
+---@return table<string, string> formatted
+---@return string[] list
+M.listKittyCompatibleFonts = function()
+  local cmd, parse, tool, hint = installed_fonts_source()
+  M.require_executable(tool, hint)
+  M.require_executable('kitty', 'kitty to list compatible fonts')
+  local outputs, codes = M.system_parallel({ cmd, kitty_fonts_cmd })
+  local result = outputs[2]
+  if codes[2] ~= 0 or not has_families(result) then
+    result = M.system(kitty_runpy_cmd)
+    if vim.v.shell_error ~= 0 then return {}, {} end
+  end
 
-  local modified_content =
-    content:gsub('font_family.-\n', 'font_family ' .. new_font_family .. '\n')
+  return M.parseKittyFonts(parse(outputs[1]), result)
+end
 
-  f = assert(io.open(require('nekifoch').config.kitty_conf_path, 'w'))
-  f:write(modified_content)
-  f:close()
+---@param callback fun(formatted: table<string, string>, list: string[])
+M.listKittyCompatibleFontsAsync = function(callback)
+  local cmd, parse = installed_fonts_source()
+  local installed, result
+  local function done()
+    if installed and result then
+      callback(M.parseKittyFonts(installed, result))
+    end
+  end
+
+  M.read_async(cmd, function(output)
+    installed = parse(output)
+    done()
+  end)
+  M.read_async(kitty_fonts_cmd, function(output)
+    if has_families(output) then
+      result = output
+      return done()
+    end
+    M.read_async(kitty_runpy_cmd, function(fallback)
+      result = fallback
+      done()
+    end)
+  end)
 end
 
----@param size integer
-M.replace_font_size = function(size)
-  local f = assert(io.open(require('nekifoch').config.kitty_conf_path, 'r'))
-  local content = f:read('*all')
-  f:close()
+---Escape a family name for use in a fontconfig pattern
+---@param family string
+---@return string
+local function fc_escape(family)
+  return (family:gsub('([\\%-:,])', '\\%1'))
+end
 
-  local modified_content =
-    content:gsub('font_size.-\n', 'font_size ' .. size .. '\n')
+-- Families known to ship programming ligatures, fontconfig can't tell
+local ligature_fonts = {
+  'fira code',
+  'firacode',
+  'jetbrains',
+  'cascadia',
+  'iosevka',
+  'victor mono',
+  'hasklig',
+  'monoid',
+  'monaspace',
+  'recursive',
+  'lilex',
+  'maple mono',
+}
 
-  f = assert(io.open(require('nekifoch').config.kitty_conf_path, 'w'))
-  f:write(modified_content)
-  f:close()
+---@class NekifochFontFace
+---@field style string
+---@field psname string PostScript name, what kitty's `font_features` takes
+---@field file string
+---@field format string Extension, e.g. `ttf`, and whether it is variable
+
+---@class NekifochFontInfo
+---@field family string
+---@field files string[]
+---@field faces NekifochFontFace[]
+---@field styles string[]
+---@field monospace boolean
+---@field nerd boolean Has Nerd Font / powerline glyphs
+---@field ligatures boolean Guessed from the family name
+---@field kitty_compatible boolean
+
+---Collect facts about an installed font family from fontconfig
+---@param family string
+---@param compatible string[] Kitty compatible families
+---@return NekifochFontInfo|nil
+M.font_info = function(family, compatible)
+  local pattern = ':family=' .. fc_escape(family)
+  local lines = M.systemlist({
+    'fc-list',
+    '--format',
+    '%{file}\t%{style[0]}\t%{spacing}\t%{postscriptname}\t%{variable}\n',
+    pattern,
+  })
+  if vim.v.shell_error ~= 0 or #lines == 0 then return nil end
+
+  local info = {
+    family = family,
+    files = {},
+    faces = {},
+    styles = {},
+    monospace = false,
+    nerd = false,
+    ligatures = false,
+    kitty_compatible = vim.tbl_contains(compatible, family),
+  }
+  for _, line in ipairs(lines) do
+    local file, style, spacing, psname, variable =
+      unpack(vim.split(line, '\t'))
+    table.insert(info.files, file)
+    table.insert(info.faces, {
+      style = style or '',
+      psname = psname or '',
+      file = file,
+      format = (file:match('%.(%w+)$') or '?'):lower()
+        .. (variable == 'True' and ' variable' or ''),
+    })
+    if style and style ~= '' and not vim.tbl_contains(info.styles, style) then
+      table.insert(info.styles, style)
+    end
+    -- 90 is dual width, 100 is mono
+    if tonumber(spacing) and tonumber(spacing) >= 90 then
+      info.monospace = true
+    end
+  end
+
+  table.sort(info.faces, function(a, b) return a.style < b.style end)
+
+  -- Powerline separator and a Font Awesome glyph
+  local nerd = M.systemlist({
+    'fc-list',
+    '--format',
+    '%{family}\n',
+    pattern .. ':charset=e0b0 f013',
+  })
+  info.nerd = vim.v.shell_error == 0 and #nerd > 0
+
+  local lower = family:lower()
+  for _, name in ipairs(ligature_fonts) do
+    if lower:find(name, 1, true) then
+      info.ligatures = true
+      break
+    end
+  end
+
+  return info
 end
 
--- Cache for installed fonts
-M.cachedInstalledFonts = nil
+---Characters of `text` no face of `family` has a glyph for, `nil` when the
+---family isn't installed
+---@param family string
+---@param text string
+---@return string[]|nil
+M.missing_glyphs = function(family, text)
+  local lines = M.systemlist({
+    'fc-list',
+    '--format',
+    '%{charset}\n',
+    ':family=' .. fc_escape(family),
+  })
+  if vim.v.shell_error ~= 0 or #lines == 0 then return nil end
 
----Get list of installed fonts with caching mechanism
----@return table
-M.getCachedInstalledFonts = function()
-  if not M.cachedInstalledFonts then
-    M.cachedInstalledFonts = M.listInstalledFonts()
+  -- Charsets are hex ranges like `20-7e a0-17f 2190`
+  local ranges = {}
+  for _, line in ipairs(lines) do
+    for first, last in line:gmatch('(%x+)%-?(%x*)') do
+      local from = tonumber(first, 16)
+      table.insert(ranges, { from, last ~= '' and tonumber(last, 16) or from })
+    end
+  end
+
+  local missing, seen = {}, {}
+  for _, code in ipairs(vim.fn.str2list(text)) do
+    -- Whitespace, zero width joiners and variation selectors draw nothing
+    local blank = code <= 0x20
+      or code == 0x200d
+      or (code >= 0xfe00 and code <= 0xfe0f)
+    if not blank and not seen[code] then
+      seen[code] = true
+      local found = false
+      for _, range in ipairs(ranges) do
+        if code >= range[1] and code <= range[2] then
+          found = true
+          break
+        end
+      end
+      if not found then table.insert(missing, vim.fn.nr2char(code)) end
+    end
   end
-  return M.cachedInstalledFonts
+  return missing
+end
+
+---@alias NekifochEditAction "'replaced'"|"'uncommented'"|"'appended'"
+
+---Include file written instead of kitty.conf with `managed.enable`
+---@return string
+M.managed_path = function()
+  local config = require('nekifoch').config
+  local dir = vim.fn.fnamemodify(config.kitty_conf_path, ':h')
+  return vim.fn.simplify(dir .. '/' .. config.managed.file)
+end
+
+---How to use managed mode with a kitty.conf nekifoch can't write
+---@return string
+M.include_hint = function()
+  local name = vim.fn.fnamemodify(M.managed_path(), ':t')
+  return ' Add "include '
+    .. name
+    .. '" where it is generated, e.g. programs.kitty.extraConfig in'
+    .. ' home-manager, and set managed.enable to write settings there.'
+end
+
+---Whether kitty.conf includes the managed file
+---@return boolean
+local function includes_managed()
+  local kitty_conf = require('nekifoch.kitty_conf')
+  local path = require('nekifoch').config.kitty_conf_path
+  local file = M.managed_path()
+  local dir = vim.fn.fnamemodify(path, ':h')
+  for _, line in ipairs(kitty_conf.read(path)) do
+    if line.kind == 'directive' and line.option == 'include' then
+      local included = vim.fn.expand(line.value)
+      if included:sub(1, 1) ~= '/' then included = dir .. '/' .. included end
+      if vim.fn.simplify(included) == file then return true end
+    end
+  end
+  return false
+end
+
+---Create the managed file and include it at the end of kitty.conf, where
+---its settings win, unless kitty.conf already includes it. A read-only
+---kitty.conf has to include it where it is generated
+local function ensure_managed()
+  local path = require('nekifoch').config.kitty_conf_path
+  local file = M.managed_path()
+  local resolved = M.resolve(path)
+  local included = includes_managed()
+  local exists = vim.loop.fs_stat(resolved) ~= nil
+  if not included and exists and not vim.loop.fs_access(resolved, 'W') then
+    error(
+      resolved
+        .. ' is read-only and does not include the file nekifoch writes.'
+        .. M.include_hint(),
+      0
+    )
+  end
+  if not pcall(M.read_file, file) then M.write_file(file, '') end
+  if included then return end
+  local kitty_conf = require('nekifoch.kitty_conf')
+  kitty_conf.append(path, 'include', vim.fn.fnamemodify(file, ':t'))
+end
+
+---Set `option` in the managed file, leaving the user's config as it is.
+---Lines there belong to nekifoch, so edits are always reported as replaced
+---@param option string
+---@param value string|nil `nil` removes it
+---@param key string|nil First word of the value telling repeated options
+---like `modify_font` apart
+---@return NekifochEditAction
+local function edit_managed(option, value, key)
+  local kitty_conf = require('nekifoch.kitty_conf')
+  ensure_managed()
+  local file = M.managed_path()
+  local lines = kitty_conf.read(file)
+  local found
+  for i, line in ipairs(lines) do
+    if
+      line.kind == 'directive'
+      and line.option == option
+      and (not key or line.value:match('^%S+') == key)
+    then
+      found = i
+    end
+  end
+
+  if not value then
+    if found then
+      table.remove(lines, found)
+      kitty_conf.write(file, lines)
+    end
+  elseif found then
+    kitty_conf.set_line(file, found, value)
+  else
+    kitty_conf.append(file, option, value)
+  end
+  return 'replaced'
+end
+
+---@return boolean
+local function managed() return require('nekifoch').config.managed.enable end
+
+---Replace `option` where it takes effect, possibly in an included file,
+---keeping the line's formatting. When it isn't set, uncomment the first
+---commented out line in kitty.conf or append the option
+---@param option string
+---@param value string
+---@return NekifochEditAction
+M.replace_option = function(option, value)
+  if managed() then return edit_managed(option, value) end
+  local kitty_conf = require('nekifoch.kitty_conf')
+  local path = require('nekifoch').config.kitty_conf_path
+  local entry = kitty_conf.effective(path)[option]
+  if entry and not entry.env then
+    kitty_conf.set_line(entry.file, entry.line, value)
+    return 'replaced'
+  end
+
+  if kitty_conf.uncomment(path, option, value) then return 'uncommented' end
+  kitty_conf.append(path, option, value)
+  return 'appended'
+end
+
+---Comment out `option` wherever it is set, following includes, so kitty's
+---default applies
+---@param option string
+M.comment_option = function(option)
+  if managed() then return edit_managed(option, nil) end
+  local path = require('nekifoch').config.kitty_conf_path
+  local kitty_conf = require('nekifoch.kitty_conf')
+  -- Earlier directives take effect once the last one is commented out
+  local entry = kitty_conf.effective(path)[option]
+  while entry and not entry.env do
+    kitty_conf.comment_line(entry.file, entry.line)
+    entry = kitty_conf.effective(path)[option]
+  end
+end
+
+---Value for a font `option` set to `family`, keeping the extended fields
+---already written and adding the ones in `spec`
+---@param option string
+---@param family string
+---@param spec table<string, string>|nil
+---@return string
+local function font_value(option, family, spec)
+  local kitty_conf = require('nekifoch.kitty_conf')
+  local path = require('nekifoch').config.kitty_conf_path
+  local entry = kitty_conf.effective(path)[option]
+  local font = kitty_conf.parse_font(entry and entry.value or family)
+  kitty_conf.set_font_field(font, 'family', family)
+  local keys = vim.tbl_keys(spec or {})
+  table.sort(keys)
+  for _, key in ipairs(keys) do
+    kitty_conf.set_font_field(font, key, spec[key])
+  end
+  return kitty_conf.format_font(font)
+end
+
+---@param option string `font_family` or `<style>_font`
+---@param family string Font family or `auto`
+---@param spec table<string, string>|nil Extended fields like `style`
+---@return NekifochEditAction
+M.replace_font = function(option, family, spec)
+  return M.replace_option(option, font_value(option, family, spec))
+end
+
+---@param style "'bold'"|"'italic'"|"'bold_italic'"
+---@param family string Font family or `auto`
+---@param spec table<string, string>|nil
+---@return NekifochEditAction
+M.replace_style_font = function(style, family, spec)
+  return M.replace_font(style .. '_font', family, spec)
+end
+
+---@return NekifochEditAction
+M.replace_font_family = function(...)
+  local new_font_family = table.concat({ ... }, ' ')
+  return M.replace_font('font_family', new_font_family)
+end
+
+-- What kitty's `modify_font` adjusts
+M.font_modifications = {
+  'cell_height',
+  'cell_width',
+  'baseline',
+  'underline_position',
+  'underline_thickness',
+  'strikethrough_position',
+  'strikethrough_thickness',
+}
+
+---`modify_font` can be repeated, the last line for `what` takes effect
+---@param what string
+---@return NekifochDirective|nil
+local function modify_font_entry(what)
+  local path = require('nekifoch').config.kitty_conf_path
+  local entry
+  for _, directive in ipairs(require('nekifoch.kitty_conf').directives(path)) do
+    if
+      directive.option == 'modify_font'
+      and directive.value:match('^%S+') == what
+    then
+      entry = directive
+    end
+  end
+  return entry
+end
+
+---Current `modify_font` value for `what`, e.g. `110%` or `2px`
+---@param what string
+---@return string|nil
+M.get_modify_font = function(what)
+  local entry = modify_font_entry(what)
+  return entry and entry.value:match('^%S+%s+(.-)%s*$')
+end
+
+---@param what string
+---@param value string
+---@return NekifochEditAction
+M.replace_modify_font = function(what, value)
+  if managed() then
+    return edit_managed('modify_font', what .. ' ' .. value, what)
+  end
+  local kitty_conf = require('nekifoch.kitty_conf')
+  local entry = modify_font_entry(what)
+  if entry and not entry.env then
+    kitty_conf.set_line(entry.file, entry.line, what .. ' ' .. value)
+    return 'replaced'
+  end
+  local path = require('nekifoch').config.kitty_conf_path
+  kitty_conf.append(path, 'modify_font', what .. ' ' .. value)
+  return 'appended'
+end
+
+---Comment out every `modify_font` line for `what`
+---@param what string
+M.comment_modify_font = function(what)
+  if managed() then return edit_managed('modify_font', nil, what) end
+  local kitty_conf = require('nekifoch.kitty_conf')
+  local entry = modify_font_entry(what)
+  while entry and not entry.env do
+    kitty_conf.comment_line(entry.file, entry.line)
+    entry = modify_font_entry(what)
+  end
+end
+
+---@param size number|string
+---@return NekifochEditAction
+M.replace_font_size = function(size)
+  return M.replace_option('font_size', size)
 end
 
 return M
@@ -0,0 +1,235 @@
+-- Checks `setup()` options against the defaults, reporting unknown keys and
+-- wrong values all at once instead of ignoring them
+local M = {}
+
+-- Options without a default, with the types they take. Config paths have
+-- none when the file wasn't found
+local optional = {
+  kitty_conf_path = { 'string' },
+  alacritty_conf_path = { 'string' },
+  windows_terminal_conf_path = { 'string' },
+  rio_conf_path = { 'string' },
+  contour_conf_path = { 'string' },
+  on_pre_change = { 'function' },
+  on_post_change = { 'function' },
+  ['window.width'] = { 'number' },
+  ['window.height'] = { 'number' },
+  ['window.row'] = { 'number', 'string' },
+  ['window.col'] = { 'number', 'string' },
+  ['window.zindex'] = { 'number' },
+}
+
+-- Tables keyed by the user's own names
+local open = {
+  profiles = true,
+  profiles_by_host = true,
+  menu_items = true,
+  rules = true,
+  by_colorscheme = true,
+  bundles = true,
+}
+
+-- Values string options accept
+local choices = {
+  terminal = {
+    'auto',
+    'kitty',
+    'alacritty',
+    'rio',
+    'contour',
+    'windows_terminal',
+    'guifont',
+  },
+  reload_strategy = { 'auto', 'signal', 'remote' },
+  picker = { 'nui', 'ui_select' },
+  cycle = { 'all', 'favorites', 'recent' },
+  ['neovide.scale'] = { 'off', 'also', 'only' },
+  ['log.level'] = { 'debug', 'info', 'warn', 'error', 'off' },
+  ['window.anchor'] = { 'center', 'cursor', 'top_right' },
+}
+
+local border_styles =
+  { 'none', 'single', 'double', 'rounded', 'solid', 'shadow' }
+local window_kinds = { 'default', 'menu', 'picker', 'input', 'info' }
+
+---Edit distance, to suggest the option a typo meant
+---@param a string
+---@param b string
+---@return integer
+local function distance(a, b)
+  local previous = {}
+  for j = 0, #b do
+    previous[j] = j
+  end
+  for i = 1, #a do
+    local current = { [0] = i }
+    for j = 1, #b do
+      local cost = a:sub(i, i) == b:sub(j, j) and 0 or 1
+      current[j] = math.min(
+        previous[j] + 1,
+        current[j - 1] + 1,
+        previous[j - 1] + cost
+      )
+    end
+    previous = current
+  end
+  return previous[#b]
+end
+
+---@param key string
+---@param known table
+---@return string
+local function unknown(key, known)
+  local best, best_distance = nil, 4
+  for name in pairs(known) do
+    local d = distance(key, name)
+    if d < best_distance then
+      best, best_distance = name, d
+    end
+  end
+  return best and (', did you mean ' .. best .. '?') or ''
+end
+
+---Backends added with `register_backend()` before `setup()`
+---@param name any
+---@return boolean
+local function registered(name)
+  return require('nekifoch.backend').registered[name] ~= nil
+end
+
+---@param value any
+---@return boolean
+local function is_border(value)
+  if type(value) == 'string' then
+    return vim.tbl_contains(border_styles, value)
+  end
+  if type(value) ~= 'table' or #value == 0 then return false end
+  for _, char in ipairs(value) do
+    if type(char) ~= 'string' and type(char) ~= 'table' then return false end
+  end
+  return true
+end
+
+---@param value any
+---@return boolean
+local function is_keys(value)
+  if type(value) == 'string' then return true end
+  if type(value) ~= 'table' then return false end
+  for _, key in ipairs(value) do
+    if type(key) ~= 'string' then return false end
+  end
+  return true
+end
+
+---@param user table
+---@param defaults table
+---@param path string Prefix of the option names, e.g. `window.`
+---@param problems string[]
+local function walk(user, defaults, path, problems)
+  for key, value in pairs(user) do
+    local name = path .. key
+    local expected = defaults[key]
+
+    if name == 'borders' then
+      local per_kind = type(value) == 'table' and not value[1]
+      if per_kind then
+        for kind, border in pairs(value) do
+          if not vim.tbl_contains(window_kinds, kind) then
+            table.insert(problems, 'borders.' .. kind .. ' is not a window')
+          elseif not is_border(border) then
+            table.insert(problems, 'borders.' .. kind .. ' is not a border')
+          end
+        end
+      elseif not is_border(value) then
+        table.insert(
+          problems,
+          'borders must be one of '
+            .. table.concat(border_styles, ', ')
+            .. ', a list of border characters or a table per window'
+        )
+      end
+    elseif name == 'targets' and type(value) == 'table' then
+      local known = vim.list_extend({ 'terminal' }, choices.terminal)
+      for _, target in ipairs(value) do
+        local valid = vim.tbl_contains(known, target) or registered(target)
+        if target == 'auto' or not valid then
+          table.insert(
+            problems,
+            'targets can only list terminal, '
+              .. table.concat(vim.list_slice(choices.terminal, 2), ', ')
+          )
+          break
+        end
+      end
+    elseif name == 'rules' and type(value) == 'table' then
+      for i, rule in ipairs(value) do
+        for _, key in ipairs({ 'after', 'before' }) do
+          local time = type(rule) == 'table' and rule[key]
+          if time and not tostring(time):match('^%d%d?:%d%d$') then
+            table.insert(
+              problems,
+              'rules[' .. i .. '].' .. key .. ' is not HH:MM'
+            )
+          end
+        end
+      end
+    elseif name == 'menu_items' and type(value) == 'table' then
+      for i, item in ipairs(value) do
+        local valid = type(item) == 'table'
+          and type(item.label) == 'string'
+          and type(item.action) == 'function'
+        if not valid then
+          table.insert(
+            problems,
+            'menu_items[' .. i .. '] needs a label and an action function'
+          )
+        end
+      end
+    elseif path == 'keymaps.' and expected ~= nil then
+      if not is_keys(value) then
+        table.insert(problems, name .. ' must be a key or a list of keys')
+      end
+    elseif expected == nil then
+      if not optional[name] then
+        table.insert(
+          problems,
+          'Unknown option ' .. name .. unknown(tostring(key), defaults)
+        )
+      elseif not vim.tbl_contains(optional[name], type(value)) then
+        table.insert(
+          problems,
+          name .. ' must be a ' .. table.concat(optional[name], ' or ')
+        )
+      end
+    elseif type(value) ~= type(expected) then
+      table.insert(
+        problems,
+        name .. ' must be a ' .. type(expected) .. ', got ' .. type(value)
+      )
+    elseif type(value) == 'table' and not expected[1] and not open[name] then
+      walk(value, expected, name .. '.', problems)
+    elseif
+      choices[name]
+      and not vim.tbl_contains(choices[name], value)
+      and not (name == 'terminal' and registered(value))
+    then
+      table.insert(
+        problems,
+        name .. ' must be one of ' .. table.concat(choices[name], ', ')
+      )
+    end
+  end
+end
+
+---Problems with the options passed to `setup()`
+---@param config table
+---@param defaults FontReplaceConfig
+---@return string[]
+M.check = function(config, defaults)
+  local problems = {}
+  walk(config, defaults, '', problems)
+  table.sort(problems)
+  return problems
+end
+
+return M
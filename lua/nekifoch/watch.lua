@@ -0,0 +1,70 @@
+-- Notice edits of the terminal config made outside this Neovim, e.g. by
+-- another instance or a dotfiles sync
+local M = {}
+
+---@type uv_fs_event_t[]
+M.watchers = {}
+
+---Directories of the config and the files it includes. Editors often
+---replace files on save, which only a directory watch survives
+---@return string[]
+local function dirs()
+  local terminal = require('nekifoch.backend').current()
+  local files = { terminal.config_path() }
+  if terminal.name == 'kitty' then
+    local ok, _, included =
+      pcall(require('nekifoch.kitty_conf').effective, files[1])
+    if ok then files = included end
+  end
+
+  local result, seen = {}, {}
+  for _, file in ipairs(files) do
+    local dir = vim.fn.fnamemodify(file, ':h')
+    -- `guifont` has no file
+    if file ~= '' and not seen[dir] and vim.fn.isdirectory(dir) == 1 then
+      seen[dir] = true
+      table.insert(result, dir)
+    end
+  end
+  return result
+end
+
+M.stop = function()
+  for _, watcher in ipairs(M.watchers) do
+    watcher:stop()
+    watcher:close()
+  end
+  M.watchers = {}
+end
+
+local scheduled = false
+
+local function on_change()
+  -- Saving touches several files, react once
+  if scheduled then return end
+  scheduled = true
+  vim.defer_fn(function()
+    scheduled = false
+    require('nekifoch.utils').invalidate()
+    -- Includes may have been added or removed
+    M.start()
+    vim.api.nvim_exec_autocmds(
+      'User',
+      { pattern = 'NekifochConfigChanged', modeline = false }
+    )
+    vim.cmd('redrawstatus!')
+  end, 200)
+end
+
+---Watch the config until `stop()`, starting over when already watching
+M.start = function()
+  M.stop()
+  for _, dir in ipairs(dirs()) do
+    local watcher = vim.loop.new_fs_event()
+    if watcher and watcher:start(dir, {}, vim.schedule_wrap(on_change)) then
+      table.insert(M.watchers, watcher)
+    end
+  end
+end
+
+return M
@@ -2,14 +2,30 @@ local wk = require('which-key')
 
 wk.register({
   ['<leader>sf'] = { name = 'Font' },
+  ['<leader>sfm'] = {
+    function() require('nekifoch.picker').menu() end,
+    'Font menu',
+  },
   ['<leader>sfl'] = { ':Nekifoch list<cr>', 'Fonts list' },
   ['<leader>sfc'] = { ':Nekifoch check<cr>', 'Check current font settings' },
   ['<leader>sff'] = {
-    function() require('nekifoch.nui_set_font')() end,
+    function() require('nekifoch.picker').font() end,
     'Set font family',
   },
+  ['<leader>sfb'] = {
+    function() require('nekifoch.picker').font('bold') end,
+    'Set bold font',
+  },
+  ['<leader>sfi'] = {
+    function() require('nekifoch.picker').font('italic') end,
+    'Set italic font',
+  },
+  ['<leader>sfI'] = {
+    function() require('nekifoch.picker').font('bold_italic') end,
+    'Set bold italic font',
+  },
   ['<leader>sfs'] = {
-    function() require('nekifoch.nui_set_size')() end,
+    function() require('nekifoch.picker').size() end,
     'Set font size',
   },
 })
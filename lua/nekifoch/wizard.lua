@@ -0,0 +1,83 @@
+-- `:Nekifoch wizard`, a change built up over the font, size and style
+-- windows and written once its diff is confirmed
+local util = require('nekifoch.utils')
+local ui = require('nekifoch.ui')
+local notify = require('nekifoch.notify')
+
+local M = {}
+
+---Change shared by the steps
+---@type NekifochFont
+M.pending = {}
+
+local function picker() return require('nekifoch.picker') end
+local function command_func() return require('nekifoch.command_func') end
+
+local function cancel()
+  local terminal = require('nekifoch.backend').current()
+  -- The family picker may have left a preview on screen
+  if terminal.cancel_preview then terminal.cancel_preview() end
+  M.pending = {}
+end
+
+local function write()
+  local command = require('nekifoch.command')
+  local ok, change, err = pcall(command_func().apply, M.pending, true)
+  M.pending = {}
+  if not ok or err then return notify.error(ok and err or change) end
+  command.formatters.apply(change)
+end
+
+local function confirm()
+  local diff = require('nekifoch.diff')
+  local ok, err = pcall(diff.confirm, M.pending, write, cancel)
+  if not ok then
+    cancel()
+    notify.error(err)
+  end
+end
+
+local function styles()
+  ---@param value string|nil `auto` to match the family, `nil` keeps them
+  local function choose(value)
+    return function()
+      for _, style in ipairs(util.styles) do
+        M.pending[style] = value
+      end
+      ui.push(styles)
+      confirm()
+    end
+  end
+  picker().choose({
+    { label = 'Match the family (auto)', action = choose('auto') },
+    { label = 'Keep current', action = choose(nil) },
+  }, 'Bold and italic fonts')
+end
+
+local function size()
+  picker().size(function(value)
+    if not tonumber(value) then
+      return notify.error('Invalid font size: ' .. value)
+    end
+    M.pending.size = tostring(command_func().clamp_size(tonumber(value)))
+    ui.push(size)
+    styles()
+  end)
+end
+
+local function family()
+  picker().font(nil, nil, function(font)
+    M.pending.font = font
+    ui.push(family)
+    size()
+  end)
+end
+
+---Start over from the family picker
+M.start = function()
+  M.pending = {}
+  ui.reset()
+  family()
+end
+
+return M
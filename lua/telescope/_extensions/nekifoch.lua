@@ -0,0 +1,54 @@
+local has_telescope, telescope = pcall(require, 'telescope')
+if not has_telescope then
+  error('nekifoch: this extension requires telescope.nvim')
+end
+
+local pickers = require('telescope.pickers')
+local finders = require('telescope.finders')
+local previewers = require('telescope.previewers')
+local actions = require('telescope.actions')
+local action_state = require('telescope.actions.state')
+local conf = require('telescope.config').values
+
+---Fuzzy find a compatible font and apply it
+---@param opts table|nil Telescope picker options
+local function fonts(opts)
+  opts = opts or {}
+  local _, compatibleFonts = require('nekifoch.cache').get()
+
+  pickers
+    .new(opts, {
+      prompt_title = 'Fonts',
+      finder = finders.new_table({ results = compatibleFonts }),
+      sorter = conf.generic_sorter(opts),
+      previewer = previewers.new_buffer_previewer({
+        title = 'Font info',
+        define_preview = function(self, entry)
+          local info =
+            require('nekifoch.utils').font_info(entry.value, compatibleFonts)
+          local lines = info
+              and require('nekifoch.command').font_info_lines(info)
+            or { 'Font not found: ' .. entry.value }
+          vim.api.nvim_buf_set_lines(self.state.bufnr, 0, -1, false, lines)
+        end,
+      }),
+      attach_mappings = function(prompt_bufnr)
+        actions.select_default:replace(function()
+          actions.close(prompt_bufnr)
+          local selection = action_state.get_selected_entry()
+          if selection then
+            require('nekifoch.command').run('set_font', { selection.value })
+          end
+        end)
+        return true
+      end,
+    })
+    :find()
+end
+
+return telescope.register_extension({
+  exports = {
+    nekifoch = fonts,
+    fonts = fonts,
+  },
+})
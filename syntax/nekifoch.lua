@@ -0,0 +1,10 @@
+-- Buffers of the Nekifoch windows
+if vim.b.current_syntax then return end
+
+vim.cmd([[
+  syntax match NekifochLabel /^\s*\zs\a[[:alnum:] _]*:\ze\s/
+  syntax match NekifochNumber /\<\d\+\%(\.\d\+\)\?\>/
+  syntax match NekifochSeparator /[│·]/
+]])
+
+vim.b.current_syntax = 'nekifoch'
@@ -0,0 +1,62 @@
+-- The size window steps with h/l/J/K and counts once <Esc> has left insert
+-- mode, a second <Esc> goes back. Needs nui.nvim, found in $NUI or lazy.nvim's
+-- directory. Run from the repository root:
+--   nvim -l tests/size_steps.lua
+package.path = './lua/?.lua;./lua/?/init.lua;' .. package.path
+vim.opt.rtp:append(
+  vim.env.NUI or (vim.fn.stdpath('data') .. '/lazy/nui.nvim')
+)
+
+---@param ok boolean
+---@param message string
+local function check(ok, message)
+  if ok then return end
+  io.stderr:write('FAIL: ' .. message .. '\n')
+  os.exit(1)
+end
+
+check(pcall(require, 'nui.input'), 'nui.nvim not found, set $NUI')
+
+-- Nothing is applied, the window only shows the stepped value
+require('nekifoch.command').run = function() end
+require('nekifoch.nui_set_size')()
+
+local ui = require('nekifoch.ui')
+local input = ui.active
+local bufnr = input.bufnr
+
+---@param keys string
+local function feed(keys)
+  keys = vim.api.nvim_replace_termcodes(keys, true, false, true)
+  vim.api.nvim_feedkeys(keys, 'xt', false)
+end
+
+---@return string
+local function value()
+  return vim.api.nvim_buf_get_lines(bufnr, 0, 1, false)[1]
+end
+
+feed('<Esc>')
+check(input.winid ~= nil, '<Esc> in insert mode closed the window')
+check(vim.fn.mode() == 'n', '<Esc> did not leave insert mode')
+
+vim.api.nvim_buf_set_lines(bufnr, 0, 1, false, { '12' })
+local steps = {
+  { 'l', '12.1' },
+  { 'h', '12' },
+  { 'K', '14' },
+  { 'J', '12' },
+  { 'k', '13' },
+  { 'j', '12' },
+  { '3k', '15' },
+}
+for _, step in ipairs(steps) do
+  feed(step[1])
+  local got = value()
+  check(got == step[2], step[1] .. ' gave ' .. got .. ', not ' .. step[2])
+end
+
+feed('<Esc>')
+check(input.winid == nil, '<Esc> in normal mode kept the window')
+
+print('ok')
@@ -0,0 +1,37 @@
+-- Writing a config reached through a chain of symlinks changes the file at
+-- its end and keeps every link. Run from the repository root:
+--   nvim -l tests/symlinks.lua
+package.path = './lua/?.lua;./lua/?/init.lua;' .. package.path
+
+local util = require('nekifoch.utils')
+
+local dir = vim.fn.tempname()
+vim.fn.mkdir(dir, 'p')
+require('nekifoch').config.backup.dir = dir .. '/backups'
+
+---@param ok boolean
+---@param message string
+local function check(ok, message)
+  if ok then return end
+  io.stderr:write('FAIL: ' .. message .. '\n')
+  vim.fn.delete(dir, 'rf')
+  os.exit(1)
+end
+
+-- outer.conf -> inner.conf -> kitty.conf
+local file = dir .. '/kitty.conf'
+local inner = dir .. '/inner.conf'
+local outer = dir .. '/outer.conf'
+vim.fn.writefile({ 'font_size 12' }, file)
+check(vim.loop.fs_symlink(file, inner) == true, 'could not link inner.conf')
+check(vim.loop.fs_symlink(inner, outer) == true, 'could not link outer.conf')
+
+check(util.resolve(outer) == util.resolve(file), 'chain not resolved')
+util.write_file(outer, 'font_size 14\n')
+
+check(vim.loop.fs_lstat(outer).type == 'link', 'outer.conf was replaced')
+check(vim.loop.fs_lstat(inner).type == 'link', 'inner.conf was replaced')
+check(util.read_file(file) == 'font_size 14\n', 'kitty.conf not written')
+
+vim.fn.delete(dir, 'rf')
+print('ok')